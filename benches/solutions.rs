@@ -0,0 +1,91 @@
+use aoc_2020::inputs::{self, InputStore};
+use aoc_2020::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use std::path::Path;
+
+// benchmarks run on the local puzzle input of the day when there is one, and
+// on the puzzle example repeated `times` times otherwise
+fn load_input(day: u32, times: usize, separator: &str) -> String {
+  let store = InputStore::from_env().unwrap_or_else(|err| panic!("{}", err));
+  if store.has_input(day) {
+    return store.load(day).unwrap_or_else(|err| panic!("{}", err));
+  }
+
+  let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/inputs");
+  let example = inputs::read_input(&examples.join(format!("day{}.txt", day)))
+    .unwrap_or_else(|err| panic!("{}", err));
+  vec![example; times].join(separator)
+}
+
+// benchmarks every generator of a day and the parts that use it, in a group
+// named after the day; days without a generator are benchmarked with `raw`
+macro_rules! bench_day {
+  ($c:expr, $day:expr, ($times:expr, $separator:expr), $module:ident {
+    $($generator:ident => [$($solver:ident),+]),+ $(,)?
+  }) => {{
+    let input = load_input($day, $times, $separator);
+    let mut group = $c.benchmark_group(stringify!($module));
+    $(
+      group.bench_function(stringify!($generator), |b| {
+        b.iter(|| $module::$generator(black_box(&input)))
+      });
+      let parsed = $module::$generator(&input).expect("Invalid benchmark input");
+      $(
+        group.bench_function(stringify!($solver), |b| {
+          b.iter(|| $module::$solver(black_box(&parsed)))
+        });
+      )+
+    )+
+    group.finish();
+  }};
+  ($c:expr, $day:expr, ($times:expr, $separator:expr), $module:ident raw [$($solver:ident),+]) => {{
+    let input = load_input($day, $times, $separator);
+    let mut group = $c.benchmark_group(stringify!($module));
+    $(
+      group.bench_function(stringify!($solver), |b| {
+        b.iter(|| $module::$solver(black_box(&input)))
+      });
+    )+
+    group.finish();
+  }};
+}
+
+fn bench_solutions(c: &mut Criterion) {
+  bench_day!(c, 1, (30, "\n"), day01 { parse_day1 => [solve_part1, solve_part2] });
+  bench_day!(c, 2, (300, "\n"), day02 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 3, (30, "\n"), day03 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 4, (50, "\n\n"), day04 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 5, (1, "\n"), day05 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 6, (100, "\n\n"), day06 {
+    parse_input_part1 => [solve_part1],
+    parse_input_part2 => [solve_part2],
+  });
+  bench_day!(c, 7, (1, "\n"), day07 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 8, (1, "\n"), day08 { parse_input => [solve_part1, solve_part2, repair_brute_force] });
+  bench_day!(c, 9, (1, "\n"), day09 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 10, (1, "\n"), day10 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 11, (9, "\n"), day11 raw [solve_part1, solve_part2]);
+  bench_day!(c, 12, (150, "\n"), day12 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 13, (1, "\n"), day13 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 14, (100, "\n"), day14 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 15, (1, "\n"), day15 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 16, (1, "\n"), day16 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 17, (1, "\n"), day17 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 18, (100, "\n"), day18 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 19, (1, "\n"), day19 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 20, (1, "\n"), day20 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 21, (50, "\n"), day21 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 22, (1, "\n"), day22 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 23, (1, "\n"), day23 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 24, (1, "\n"), day24 { parse_input => [solve_part1, solve_part2] });
+  bench_day!(c, 25, (1, "\n"), day25 { parse_input => [solve_part1] });
+}
+
+// some parts take half a second per run, so take the fewest samples allowed
+criterion_group! {
+  name = benches;
+  config = Criterion::default().sample_size(10);
+  targets = bench_solutions
+}
+criterion_main!(benches);
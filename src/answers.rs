@@ -0,0 +1,109 @@
+use crate::error::AocError;
+use std::collections::HashSet;
+
+// an entry of the answers manifest: the answer a part of a day should give
+// for the local input of that day
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedAnswer {
+  pub day: u32,
+  pub part: u32,
+  pub answer: String,
+}
+
+// the manifest has one `day part answer` entry per line, separated by
+// whitespace; blank lines and lines starting with `#` are ignored
+pub fn parse_answers(input: &str) -> Result<Vec<ExpectedAnswer>, AocError> {
+  let mut answers: Vec<ExpectedAnswer> = vec![];
+  let mut seen: HashSet<(u32, u32)> = HashSet::new();
+
+  for (i, line) in input.lines().enumerate() {
+    if line.trim().is_empty() || line.trim_start().starts_with('#') {
+      continue;
+    }
+
+    // pair each field with its column, searching after the previous field so
+    // repeated values get their own position
+    let fields: Vec<(usize, &str)> = line
+      .split_whitespace()
+      .scan(0, |offset, field| {
+        let start = *offset + line[*offset..].find(field)?;
+        *offset = start + field.len();
+        Some((start + 1, field))
+      })
+      .collect();
+
+    let number = |(column, text): (usize, &str), what: &str| {
+      text
+        .parse::<u32>()
+        .map_err(|_| AocError::parse(i + 1, column, &format!("Invalid {} `{}`", what, text)))
+    };
+
+    let entry = match fields.as_slice() {
+      [day, part, (_, answer)] => ExpectedAnswer {
+        day: number(*day, "day")?,
+        part: number(*part, "part")?,
+        answer: answer.to_string(),
+      },
+      _ => {
+        let error_msg = format!("Expected `day part answer`, found `{}`", line.trim());
+        return Err(AocError::parse(i + 1, 1, &error_msg));
+      }
+    };
+
+    if !seen.insert((entry.day, entry.part)) {
+      let error_msg = format!(
+        "Duplicated answer for day {} part {}",
+        entry.day, entry.part
+      );
+      return Err(AocError::parse(i + 1, 1, &error_msg));
+    }
+    answers.push(entry);
+  }
+
+  Ok(answers)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_answers() {
+    let input = "# day part answer\n1 1 514579\n\n  21 2\tmxmxvkd,sqjhc,fvjkl\n";
+    assert_eq!(
+      parse_answers(input),
+      Ok(vec![
+        ExpectedAnswer {
+          day: 1,
+          part: 1,
+          answer: "514579".to_string()
+        },
+        ExpectedAnswer {
+          day: 21,
+          part: 2,
+          answer: "mxmxvkd,sqjhc,fvjkl".to_string()
+        },
+      ])
+    );
+  }
+
+  #[test]
+  fn test_parse_answers_errors() {
+    assert_eq!(
+      parse_answers("1 1 514579\n1 x 241861950"),
+      Err(AocError::parse(2, 3, "Invalid part `x`"))
+    );
+    assert_eq!(
+      parse_answers("1 1"),
+      Err(AocError::parse(
+        1,
+        1,
+        "Expected `day part answer`, found `1 1`"
+      ))
+    );
+    assert_eq!(
+      parse_answers("1 1 514579\n1 1 514579"),
+      Err(AocError::parse(2, 1, "Duplicated answer for day 1 part 1"))
+    );
+  }
+}
@@ -0,0 +1,72 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignmentError<K> {
+  Ambiguous(K),  // the key still has more than one candidate left
+  Impossible(K), // the key ran out of candidates
+}
+
+// finds a one-to-one assignment by elimination: keys that only have one
+// candidate are resolved, and that value is removed from the rest of keys
+pub fn resolve_unique<K, V>(
+  candidates: &BTreeMap<K, BTreeSet<V>>,
+) -> Result<BTreeMap<K, V>, AssignmentError<K>>
+where
+  K: Ord + Clone,
+  V: Ord + Clone,
+{
+  let mut pending = candidates.clone();
+  let mut resolved: BTreeMap<K, V> = BTreeMap::new();
+
+  while let Some((key, value)) = pending
+    .iter()
+    .find(|(_, values)| values.len() == 1)
+    .map(|(key, values)| (key.clone(), values.iter().next().unwrap().clone()))
+  {
+    pending.remove(&key);
+    for values in pending.values_mut() {
+      values.remove(&value);
+    }
+    resolved.insert(key, value);
+  }
+
+  match pending.iter().next() {
+    None => Ok(resolved),
+    Some((key, values)) if values.is_empty() => Err(AssignmentError::Impossible(key.clone())),
+    Some((key, _)) => Err(AssignmentError::Ambiguous(key.clone())),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn build_candidates(list: &[(u32, &[char])]) -> BTreeMap<u32, BTreeSet<char>> {
+    list
+      .iter()
+      .map(|(key, values)| (*key, values.iter().copied().collect()))
+      .collect()
+  }
+
+  #[test]
+  fn test_resolve_unique() {
+    let candidates = build_candidates(&[(0, &['a', 'b']), (1, &['b']), (2, &['a', 'b', 'c'])]);
+    let expected: BTreeMap<u32, char> = [(0, 'a'), (1, 'b'), (2, 'c')].iter().copied().collect();
+    assert_eq!(resolve_unique(&candidates), Ok(expected));
+  }
+
+  #[test]
+  fn test_resolve_unique_errors() {
+    let candidates = build_candidates(&[(0, &['a', 'b']), (1, &['a', 'b'])]);
+    assert_eq!(
+      resolve_unique(&candidates),
+      Err(AssignmentError::Ambiguous(0))
+    );
+
+    let candidates = build_candidates(&[(0, &['a']), (1, &['a']), (2, &['a', 'b'])]);
+    assert_eq!(
+      resolve_unique(&candidates),
+      Err(AssignmentError::Impossible(1))
+    );
+  }
+}
@@ -0,0 +1,496 @@
+use aoc_2020::day08::{self, Breakpoint, Debugger, Instruction, Pause};
+use aoc_2020::error::AocError;
+use aoc_2020::inputs::{self, InputStore};
+use aoc_2020::registry;
+
+use std::env;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use std::process;
+use std::time::Instant;
+
+const USAGE: &str = "Usage:
+  aoc --day N [--part P] [--input FILE]
+  aoc --all [--input-dir DIR] [--user NAME]
+  aoc debug FILE [--break SPEC]...
+  aoc analyze FILE
+
+Options:
+  --day N          run the solutions for day N
+  --part P         run only part P (1 or 2) of the day
+  --input FILE     read the puzzle input from FILE instead of stdin
+  --all            run every implemented day, reading DIR/dayN.txt
+  --input-dir DIR  directory with the inputs for --all (default: inputs)
+  --user NAME      read the inputs for --all from DIR/NAME/dayN.txt
+  --help           print this message
+
+`aoc debug` loads a day 8 handheld program from FILE, which may use
+labels, comments and the extended instructions, and steps through it
+with commands read from stdin; type `help` there for the list.
+`aoc analyze` prints which instructions of such a program are never
+reached, which of them form loops, and the swaps that make it end.
+
+The defaults of --input-dir and --user can also be set with the
+AOC_INPUT_DIR and AOC_USER env vars, or in an aoc.conf file:
+
+  input_dir = input/2020
+  user = NAME";
+
+#[derive(Debug, Default)]
+struct Options {
+  day: Option<u32>,
+  part: Option<u32>,
+  input: Option<String>,
+  input_dir: Option<String>,
+  user: Option<String>,
+  all: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+  let mut options = Options::default();
+  let mut args = args.iter();
+
+  while let Some(arg) = args.next() {
+    let mut value = || {
+      args
+        .next()
+        .cloned()
+        .ok_or(format!("Missing value for {}", arg))
+    };
+    let number = |text: String| {
+      text
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid value `{}` for {}", text, arg))
+    };
+
+    match arg.as_str() {
+      "--day" => options.day = Some(number(value()?)?),
+      "--part" => options.part = Some(number(value()?)?),
+      "--input" => options.input = Some(value()?),
+      "--input-dir" => options.input_dir = Some(value()?),
+      "--user" => options.user = Some(value()?),
+      "--all" => options.all = true,
+      _ => return Err(format!("Unrecognized argument `{}`", arg)),
+    }
+  }
+
+  if options.all == options.day.is_some() {
+    return Err("Either --day or --all is required".to_string());
+  }
+  if options.all && (options.part.is_some() || options.input.is_some()) {
+    return Err("--part and --input can't be used with --all".to_string());
+  }
+  if !options.all && (options.input_dir.is_some() || options.user.is_some()) {
+    return Err("--input-dir and --user can only be used with --all".to_string());
+  }
+
+  Ok(options)
+}
+
+fn read_input(path: Option<&Path>) -> Result<String, String> {
+  if let Some(path) = path {
+    return inputs::read_input(path).map_err(|err| err.to_string());
+  }
+
+  let mut input = String::new();
+  io::stdin()
+    .read_to_string(&mut input)
+    .map_err(|err| format!("Could not read stdin: {}", err))?;
+  Ok(inputs::normalize(&input))
+}
+
+// returns false if any of the parts failed
+fn run_day(day: u32, part: Option<u32>, input: &str) -> bool {
+  let mut success = true;
+
+  for solution in registry::find_solutions(day, part) {
+    let start = Instant::now();
+    let result = (solution.run)(input);
+    let elapsed = start.elapsed();
+    let label = format!("Day {} - Part {} ({})", day, solution.part, solution.name);
+
+    match result {
+      Ok(answer) => println!("{}: {} ({:?})", label, answer, elapsed),
+      Err(err) => {
+        eprintln!("{}: {}", label, err);
+        success = false;
+      }
+    }
+  }
+
+  success
+}
+
+fn run(options: &Options) -> Result<bool, String> {
+  if options.all {
+    let store = match &options.input_dir {
+      Some(dir) => InputStore::new(dir),
+      None => InputStore::from_env().map_err(|err| err.to_string())?,
+    };
+    let store = match &options.user {
+      Some(user) => store.with_user(user),
+      None => store,
+    };
+    let mut days: Vec<u32> = registry::solutions().map(|x| x.day).collect();
+    days.dedup();
+
+    let mut success = true;
+    for day in days {
+      match store.load(day) {
+        Ok(input) => success &= run_day(day, None, &input),
+        Err(err @ AocError::MissingInput { .. }) => eprintln!("Day {}: skipped. {}", day, err),
+        Err(err) => return Err(err.to_string()),
+      }
+    }
+
+    return Ok(success);
+  }
+
+  let day = options.day.unwrap_or_default();
+  if registry::find_solutions(day, options.part).next().is_none() {
+    return Err(match options.part {
+      Some(part) => format!("Day {} part {} is not implemented", day, part),
+      None => format!("Day {} is not implemented", day),
+    });
+  }
+
+  let input = read_input(options.input.as_deref().map(Path::new))?;
+  Ok(run_day(day, options.part, &input))
+}
+
+const DEBUG_HELP: &str = "Commands:
+  s, step [N]       run the next N instructions (default: 1)
+  c, continue       run until a breakpoint is hit or the program ends
+  b, break [SPEC]   add a breakpoint, or list them without SPEC
+  d, delete SPEC    remove a breakpoint
+  p, print          show the instruction pointer and the accumulator
+  r, registers      show the registers and the output so far
+  l, list           show the program, marking ip (>) and breakpoints (*)
+  t, trace [N]      show the last N instructions run (default: all)
+  h, help           print this message
+  q, quit           exit the debugger
+
+A breakpoint SPEC is an instruction index, like `4`, or a condition on
+the accumulator, like `acc>10`, `acc<0` or `acc=5`. Breakpoints are
+checked after each instruction runs.";
+
+fn parse_debug_args(args: &[String]) -> Result<(String, Vec<Breakpoint>), String> {
+  let mut file = None;
+  let mut breakpoints = vec![];
+  let mut args = args.iter();
+
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--break" => {
+        let spec = args.next().ok_or("Missing value for --break")?;
+        breakpoints.push(spec.parse()?);
+      }
+      _ if arg.starts_with("--") || file.is_some() => {
+        return Err(format!("Unrecognized argument `{}`", arg))
+      }
+      _ => file = Some(arg.clone()),
+    }
+  }
+
+  let file = file.ok_or("A program file is required")?;
+  Ok((file, breakpoints))
+}
+
+fn format_state(debugger: &Debugger) -> String {
+  let machine = debugger.machine();
+  let next = match debugger.program().get(machine.ip()) {
+    Some(instruction) => format!("next: {}", day08::format_instruction(instruction)),
+    None => "program ended".to_string(),
+  };
+  format!(
+    "ip = {}, acc = {} ({})",
+    machine.ip(),
+    machine.accumulator(),
+    next
+  )
+}
+
+// runs a debugger command, returning what to print, or none to quit
+fn debug_command(debugger: &mut Debugger, line: &str) -> Option<String> {
+  let tokens: Vec<&str> = line.split_whitespace().collect();
+  let (command, arg) = match tokens.as_slice() {
+    [] => return Some(String::new()),
+    [command] => (*command, None),
+    [command, rest @ ..] => (*command, Some(rest.join(" "))),
+  };
+  let count = |arg: Option<String>, default: usize| match arg {
+    Some(text) => text
+      .parse::<usize>()
+      .map_err(|_| format!("Invalid count `{}`", text)),
+    None => Ok(default),
+  };
+
+  let output = match command {
+    "s" | "step" => match count(arg, 1) {
+      Ok(n) => {
+        let mut lines = vec![];
+        for _ in 0..n {
+          match debugger.step() {
+            Ok(entry) => lines.push(entry.to_string()),
+            Err(err) => {
+              lines.push(err.to_string());
+              break;
+            }
+          }
+        }
+        lines.join("\n")
+      }
+      Err(err) => err,
+    },
+    "c" | "continue" => match debugger.run_until_break() {
+      Ok(Pause::Breakpoint(breakpoint)) => {
+        format!("Breakpoint {}: {}", breakpoint, format_state(debugger))
+      }
+      Ok(Pause::Ended) => format!("Program ended: {}", format_state(debugger)),
+      Err(err) => format!("{}: {}", err, format_state(debugger)),
+    },
+    "b" | "break" => match arg.map(|x| x.parse::<Breakpoint>()) {
+      Some(Ok(breakpoint)) => {
+        let output = format!("Breakpoint {} added", breakpoint);
+        debugger.add_breakpoint(breakpoint);
+        output
+      }
+      Some(Err(err)) => err,
+      None if debugger.breakpoints().is_empty() => "No breakpoints".to_string(),
+      None => debugger
+        .breakpoints()
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join("\n"),
+    },
+    "d" | "delete" => match arg.map(|x| x.parse::<Breakpoint>()) {
+      Some(Ok(breakpoint)) if debugger.remove_breakpoint(&breakpoint) => {
+        format!("Breakpoint {} removed", breakpoint)
+      }
+      Some(Ok(breakpoint)) => format!("No breakpoint {}", breakpoint),
+      Some(Err(err)) => err,
+      None => "Missing breakpoint to delete".to_string(),
+    },
+    "p" | "print" => format_state(debugger),
+    "r" | "registers" => {
+      let machine = debugger.machine();
+      let registers: Vec<String> = machine
+        .registers()
+        .iter()
+        .enumerate()
+        .map(|(i, x)| format!("r{} = {}", i, x))
+        .collect();
+      format!("{}\noutput: {:?}", registers.join(", "), machine.output())
+    }
+    "l" | "list" => {
+      let machine = debugger.machine();
+      let breakpoints = debugger.breakpoints();
+      let lines: Vec<String> = debugger
+        .program()
+        .iter()
+        .enumerate()
+        .map(|(i, instruction)| {
+          let current = if i == machine.ip() { '>' } else { ' ' };
+          let breakpoint = if breakpoints.contains(&Breakpoint::Instruction(i)) {
+            '*'
+          } else {
+            ' '
+          };
+          let instruction = day08::format_instruction(instruction);
+          format!("{}{} #{:<4} {}", current, breakpoint, i, instruction)
+        })
+        .collect();
+      lines.join("\n")
+    }
+    "t" | "trace" => {
+      let trace = debugger.trace();
+      match count(arg, trace.len()) {
+        Ok(n) => trace[trace.len() - n.min(trace.len())..]
+          .iter()
+          .map(|x| x.to_string())
+          .collect::<Vec<String>>()
+          .join("\n"),
+        Err(err) => err,
+      }
+    }
+    "h" | "help" => DEBUG_HELP.to_string(),
+    "q" | "quit" => return None,
+    _ => format!("Unknown command `{}`, type `help` for the list", command),
+  };
+
+  Some(output)
+}
+
+type Subcommand = fn(&[String]) -> Result<(), String>;
+
+fn load_program(file: &str) -> Result<Vec<Instruction>, String> {
+  let input = inputs::read_input(Path::new(file)).map_err(|err| err.to_string())?;
+  day08::assemble(&input).map_err(|err| err.to_string())
+}
+
+fn run_analysis(args: &[String]) -> Result<(), String> {
+  let file = match args {
+    [file] if !file.starts_with("--") => file,
+    [] => return Err("A program file is required".to_string()),
+    _ => return Err(format!("Unrecognized argument `{}`", args[args.len() - 1])),
+  };
+
+  println!("{}", day08::analyze(&load_program(file)?));
+  Ok(())
+}
+
+fn run_debugger(args: &[String]) -> Result<(), String> {
+  let (file, breakpoints) = parse_debug_args(args)?;
+  let program = load_program(&file)?;
+
+  let mut debugger = Debugger::new(&program);
+  for breakpoint in breakpoints {
+    debugger.add_breakpoint(breakpoint);
+  }
+  println!(
+    "Loaded {} instructions. Type `help` for the list of commands.",
+    program.len()
+  );
+  println!("{}", format_state(&debugger));
+
+  let stdin = io::stdin();
+  let mut lines = stdin.lock().lines();
+  loop {
+    print!("(day8) ");
+    io::stdout().flush().map_err(|err| err.to_string())?;
+    let line = match lines.next() {
+      Some(line) => line.map_err(|err| format!("Could not read stdin: {}", err))?,
+      None => return Ok(()),
+    };
+    match debug_command(&mut debugger, &line) {
+      Some(output) if output.is_empty() => {}
+      Some(output) => println!("{}", output),
+      None => return Ok(()),
+    }
+  }
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  if args.iter().any(|x| x == "--help" || x == "-h") {
+    println!("{}", USAGE);
+    return;
+  }
+
+  let subcommand: Option<Subcommand> = match args.first().map(|x| x.as_str()) {
+    Some("debug") => Some(run_debugger),
+    Some("analyze") => Some(run_analysis),
+    _ => None,
+  };
+  if let Some(subcommand) = subcommand {
+    if let Err(err) = subcommand(&args[1..]) {
+      eprintln!("{}", err);
+      process::exit(1);
+    }
+    return;
+  }
+
+  let options = parse_args(&args).unwrap_or_else(|err| {
+    eprintln!("{}\n\n{}", err, USAGE);
+    process::exit(2);
+  });
+
+  match run(&options) {
+    Ok(true) => {}
+    Ok(false) => process::exit(1),
+    Err(err) => {
+      eprintln!("{}", err);
+      process::exit(1);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(|x| x.to_string()).collect()
+  }
+
+  #[test]
+  fn test_parse_args() {
+    let options = parse_args(&args("--day 3 --part 2 --input day3.txt")).unwrap();
+    assert_eq!(options.day, Some(3));
+    assert_eq!(options.part, Some(2));
+    assert_eq!(options.input, Some("day3.txt".to_string()));
+    assert!(!options.all);
+
+    let options = parse_args(&args("--all --input-dir inputs --user alice")).unwrap();
+    assert!(options.all);
+    assert_eq!(options.input_dir, Some("inputs".to_string()));
+    assert_eq!(options.user, Some("alice".to_string()));
+  }
+
+  #[test]
+  fn test_parse_args_errors() {
+    assert!(parse_args(&args("")).is_err());
+    assert!(parse_args(&args("--day")).is_err());
+    assert!(parse_args(&args("--day x")).is_err());
+    assert!(parse_args(&args("--day 1 --all")).is_err());
+    assert!(parse_args(&args("--all --part 1")).is_err());
+    assert!(parse_args(&args("--day 1 --input-dir inputs")).is_err());
+    assert!(parse_args(&args("--day 1 --user alice")).is_err());
+    assert!(parse_args(&args("--day 1 --verbose")).is_err());
+  }
+
+  #[test]
+  fn test_parse_debug_args() {
+    assert_eq!(
+      parse_debug_args(&args("day8.txt --break 4 --break acc>10")),
+      Ok((
+        "day8.txt".to_string(),
+        vec![Breakpoint::Instruction(4), Breakpoint::AccumulatorAbove(10)]
+      ))
+    );
+    assert!(parse_debug_args(&args("")).is_err());
+    assert!(parse_debug_args(&args("day8.txt --break")).is_err());
+    assert!(parse_debug_args(&args("day8.txt --break x")).is_err());
+    assert!(parse_debug_args(&args("day8.txt other.txt")).is_err());
+  }
+
+  #[test]
+  fn test_debug_command() {
+    let program = day08::parse_input("nop +0\nacc +1\njmp -1").unwrap();
+    let mut debugger = Debugger::new(&program);
+    let mut run = |line: &str| debug_command(&mut debugger, line);
+
+    assert_eq!(
+      run("break acc=1"),
+      Some("Breakpoint acc=1 added".to_string())
+    );
+    assert_eq!(
+      run("step 2"),
+      Some("#0    nop +0     acc = 0\n#1    acc +1     acc = 1".to_string())
+    );
+    assert_eq!(
+      run("continue"),
+      Some("Breakpoint acc=1: ip = 1, acc = 1 (next: acc +1)".to_string())
+    );
+    assert_eq!(
+      run("c"),
+      Some(
+        "Infinite loop, instruction #1 would run twice: ip = 1, acc = 1 (next: acc +1)".to_string()
+      )
+    );
+    assert_eq!(run("trace 1"), Some("#2    jmp -1     acc = 1".to_string()));
+    assert_eq!(
+      run("list"),
+      Some("   #0    nop +0\n>  #1    acc +1\n   #2    jmp -1".to_string())
+    );
+    assert_eq!(
+      run("registers"),
+      Some("r0 = 0, r1 = 0, r2 = 0, r3 = 0\noutput: []".to_string())
+    );
+    assert_eq!(run("d 3"), Some("No breakpoint 3".to_string()));
+    assert_eq!(run(""), Some(String::new()));
+    assert!(run("jump").unwrap().starts_with("Unknown command"));
+    assert_eq!(run("q"), None);
+  }
+}
@@ -1,62 +1,87 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::{parse_lines, AocError};
+
 #[aoc_generator(day1)]
-pub fn parse_day1(input: &str) -> Vec<u32> {
-  return input.lines().map(|x| x.parse().unwrap()).collect();
+pub fn parse_day1(input: &str) -> Result<Vec<u32>, AocError> {
+  parse_lines(input, |x| {
+    x.parse::<u32>()
+      .map_err(|_| format!("Invalid expense `{}`", x))
+  })
 }
 
 #[aoc(day1, part1)]
-pub fn solve_part1(entries: &[u32]) -> u32 {
+pub fn solve_part1(entries: &[u32]) -> Result<u32, AocError> {
   const TARGET: u32 = 2020;
 
+  // a pair that overflows adds up to more than the target anyway; the
+  // products fit since the entries add up to the target
   for i in 0..entries.len() {
     for j in (i + 1)..entries.len() {
-      let sum = entries[i] + entries[j];
-      if sum == TARGET {
-        return entries[i] * entries[j];
+      if entries[i].checked_add(entries[j]) == Some(TARGET) {
+        return Ok(entries[i] * entries[j]);
       }
     }
   }
 
-  unreachable!();
+  Err(AocError::NoSolution(
+    "No pair of entries adds up to 2020".to_string(),
+  ))
 }
 
 #[aoc(day1, part2)]
-pub fn solve_part2(entries: &[u32]) -> u32 {
+pub fn solve_part2(entries: &[u32]) -> Result<u32, AocError> {
   const TARGET: u32 = 2020;
 
   for i in 0..entries.len() {
     for j in (i + 1)..entries.len() {
-      if entries[i] + entries[j] > TARGET {
-        continue;
-      }
+      let partial = match entries[i].checked_add(entries[j]) {
+        Some(partial) if partial <= TARGET => partial,
+        _ => continue,
+      };
 
       for k in (j + 1)..entries.len() {
-        let sum = entries[i] + entries[j] + entries[k];
-        if sum == TARGET {
-          return entries[i] * entries[j] * entries[k];
+        if partial.checked_add(entries[k]) == Some(TARGET) {
+          return Ok(entries[i] * entries[j] * entries[k]);
         }
       }
     }
   }
 
-  unreachable!();
+  Err(AocError::NoSolution(
+    "No three entries add up to 2020".to_string(),
+  ))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn test_parse_day1() {
+    assert_eq!(parse_day1("1721\n979"), Ok(vec![1721, 979]));
+    assert_eq!(
+      parse_day1("1721\n97x"),
+      Err(AocError::parse(2, 1, "Invalid expense `97x`"))
+    );
+  }
+
   #[test]
   fn test_part1() {
     let input: Vec<u32> = vec![1721, 979, 366, 299, 675, 1456];
-    assert_eq!(solve_part1(&input), 514579);
+    assert_eq!(solve_part1(&input), Ok(514579));
+    assert!(solve_part1(&[1, 2]).is_err());
+    assert_eq!(solve_part1(&[u32::MAX, 1, 2019]), Ok(2019));
+    assert!(solve_part1(&[u32::MAX, 1]).is_err());
   }
 
   #[test]
   fn test_part2() {
     let input: Vec<u32> = vec![1721, 979, 366, 299, 675, 1456];
-    assert_eq!(solve_part2(&input), 241861950);
+    assert_eq!(solve_part2(&input), Ok(241861950));
+    assert!(solve_part2(&[1, 2, 3]).is_err());
+    assert_eq!(solve_part2(&[u32::MAX, 1, 1, 2018]), Ok(2018));
+    assert!(solve_part2(&[u32::MAX, u32::MAX, 1]).is_err());
   }
 }
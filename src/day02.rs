@@ -3,6 +3,8 @@ use aoc_runner_derive::aoc_generator;
 use lazy_static::lazy_static;
 use regex::Regex;
 
+use crate::error::{parse_lines, AocError};
+
 struct PolicyConfig {
   num1: u32,
   num2: u32,
@@ -11,15 +13,16 @@ struct PolicyConfig {
 
 type Validator = Box<dyn Fn(&str) -> bool>;
 
-fn parse_policy(raw_policy: &str) -> PolicyConfig {
+fn parse_policy(raw_policy: &str) -> Result<PolicyConfig, String> {
   lazy_static! { // use lazy_static to compile the regex only once
     static ref PARSER: Regex =
-      Regex::new(r"(?P<num1>\d+)\-(?P<num2>\d+)\s(?P<character>\w)").unwrap();
+      Regex::new(r"^(?P<num1>\d+)\-(?P<num2>\d+)\s(?P<character>\w)$").unwrap();
   }
 
-  let captured = PARSER.captures(raw_policy).unwrap();
-  let num1 = captured.name("num1").unwrap().as_str().parse().unwrap();
-  let num2 = captured.name("num2").unwrap().as_str().parse().unwrap();
+  let error_msg = format!("Invalid policy `{}`", raw_policy);
+  let captured = PARSER.captures(raw_policy).ok_or(&error_msg)?;
+  let num1 = captured["num1"].parse().map_err(|_| &error_msg)?;
+  let num2 = captured["num2"].parse().map_err(|_| &error_msg)?;
   let character = captured
     .name("character")
     .unwrap()
@@ -28,83 +31,88 @@ fn parse_policy(raw_policy: &str) -> PolicyConfig {
     .next()
     .unwrap();
 
-  return PolicyConfig {
-    num1: num1,
-    num2: num2,
-    character: character,
-  };
+  Ok(PolicyConfig {
+    num1,
+    num2,
+    character,
+  })
 }
 
-fn build_old_validator(raw_policy: &str) -> Validator {
+fn build_old_validator(raw_policy: &str) -> Result<Validator, String> {
   // parse the policy to get its config
-  let policy = parse_policy(raw_policy);
+  let policy = parse_policy(raw_policy)?;
 
   // build a function from that config
-  return Box::new(move |text| {
+  Ok(Box::new(move |text| {
     let amount = text
       .chars()
       .filter(|&x| x == policy.character)
       .collect::<String>()
       .len() as u32;
     amount >= policy.num1 && amount <= policy.num2
-  });
+  }))
 }
 
-fn build_new_validator(raw_policy: &str) -> Validator {
+fn build_new_validator(raw_policy: &str) -> Result<Validator, String> {
   // parse the policy to get its config
-  let policy = parse_policy(raw_policy);
+  let policy = parse_policy(raw_policy)?;
 
   // build a function from that config
-  return Box::new(move |text| {
-    let is_at_1st_pos = text.chars().nth((policy.num1 as usize) - 1).unwrap() == policy.character;
-    let is_at_2nd_pos = text.chars().nth((policy.num2 as usize) - 1).unwrap() == policy.character;
+  Ok(Box::new(move |text| {
+    // positions are 1-based, and the ones outside the password never match
+    let is_at = |pos: u32| {
+      (pos as usize)
+        .checked_sub(1)
+        .and_then(|i| text.chars().nth(i))
+        == Some(policy.character)
+    };
 
-    is_at_1st_pos ^ is_at_2nd_pos
-  });
+    is_at(policy.num1) ^ is_at(policy.num2)
+  }))
 }
 
 pub fn is_valid_password(
   policy: &str,
   password: &str,
-  validator_builder: fn(&str) -> Validator,
-) -> bool {
-  let validator = validator_builder(policy);
-  return validator(password);
+  validator_builder: fn(&str) -> Result<Validator, String>,
+) -> Result<bool, String> {
+  let validator = validator_builder(policy)?;
+  Ok(validator(password))
 }
 
 #[aoc_generator(day2)]
-pub fn parse_input(input: &str) -> Vec<(String, String)> {
-  input
-    .lines()
-    .map(|line| {
-      let entry: Vec<&str> = line.split(':').map(|x| x.trim()).collect();
-      return (entry[0].to_string(), entry[1].to_string());
-    })
-    .collect()
+pub fn parse_input(input: &str) -> Result<Vec<(String, String)>, AocError> {
+  parse_lines(input, |line| {
+    let entry: Vec<&str> = line.split(':').map(|x| x.trim()).collect();
+    if entry.len() != 2 {
+      return Err(format!("Invalid entry `{}`", line));
+    }
+
+    // check the policy here so that bad input is reported with its line
+    parse_policy(entry[0])?;
+    Ok((entry[0].to_string(), entry[1].to_string()))
+  })
 }
 
-#[aoc(day2, part1)]
-pub fn solve_part1(entries: &[(String, String)]) -> u32 {
-  entries.iter().fold(0, |total, entry| {
-    let result = if is_valid_password(&entry.0, &entry.1, build_old_validator) {
-      1
-    } else {
-      0
-    };
-    total + result
+fn count_valid(
+  entries: &[(String, String)],
+  validator_builder: fn(&str) -> Result<Validator, String>,
+) -> Result<u32, AocError> {
+  entries.iter().try_fold(0, |total, entry| {
+    let is_valid =
+      is_valid_password(&entry.0, &entry.1, validator_builder).map_err(AocError::InvalidInput)?;
+    Ok(if is_valid { total + 1 } else { total })
   })
 }
 
+#[aoc(day2, part1)]
+pub fn solve_part1(entries: &[(String, String)]) -> Result<u32, AocError> {
+  count_valid(entries, build_old_validator)
+}
+
 #[aoc(day2, part2)]
-pub fn solve_part2(entries: &[(String, String)]) -> u32 {
-  entries.iter().fold(0, |total, entry| {
-    let result = if is_valid_password(&entry.0, &entry.1, build_new_validator) {
-      1
-    } else {
-      0
-    };
-    total + result
-  })
+pub fn solve_part2(entries: &[(String, String)]) -> Result<u32, AocError> {
+  count_valid(entries, build_new_validator)
 }
 
 #[cfg(test)]
@@ -115,22 +123,35 @@ mod tests {
   fn test_is_valid_password_with_old_policy() {
     assert_eq!(
       is_valid_password("1-3 a", "abcde", build_old_validator),
-      true
+      Ok(true)
     );
     assert_eq!(
       is_valid_password("1-3 b", "cdefg", build_old_validator),
-      false
+      Ok(false)
     );
     assert_eq!(
       is_valid_password("2-9 c", "ccccccccc", build_old_validator),
-      true
+      Ok(true)
     );
     assert_eq!(
       is_valid_password("2-2 a", "fafa", build_old_validator),
-      true
+      Ok(true)
     );
   }
 
+  #[test]
+  fn test_parse_input() {
+    assert_eq!(
+      parse_input("1-3 a: abcde"),
+      Ok(vec![("1-3 a".to_string(), "abcde".to_string())])
+    );
+    assert_eq!(
+      parse_input("1-3 a: abcde\n1-x b: cdefg"),
+      Err(AocError::parse(2, 1, "Invalid policy `1-x b`"))
+    );
+    assert!(parse_input("1-3 a abcde").is_err());
+  }
+
   #[test]
   fn test_solve_part1() {
     let entries: [(String, String); 3] = [
@@ -138,26 +159,34 @@ mod tests {
       ("1-3 b".to_string(), "cdefg".to_string()),
       ("2-9 c".to_string(), "ccccccccc".to_string()),
     ];
-    assert_eq!(solve_part1(&entries), 2);
+    assert_eq!(solve_part1(&entries), Ok(2));
   }
 
   #[test]
   fn test_is_valid_password_with_new_policy() {
     assert_eq!(
       is_valid_password("1-3 a", "abcde", build_new_validator),
-      true
+      Ok(true)
     );
     assert_eq!(
       is_valid_password("1-3 b", "cdefg", build_new_validator),
-      false
+      Ok(false)
     );
     assert_eq!(
       is_valid_password("2-9 c", "ccccccccc", build_new_validator),
-      false
+      Ok(false)
     );
     assert_eq!(
       is_valid_password("1-4 a", "fafa", build_new_validator),
-      true
+      Ok(true)
+    );
+    assert_eq!(
+      is_valid_password("1-9 a", "fafa", build_new_validator),
+      Ok(false)
+    );
+    assert_eq!(
+      is_valid_password("0-2 a", "fafa", build_new_validator),
+      Ok(true)
     );
   }
 
@@ -168,6 +197,6 @@ mod tests {
       ("1-3 b".to_string(), "cdefg".to_string()),
       ("2-9 c".to_string(), "ccccccccc".to_string()),
     ];
-    assert_eq!(solve_part2(&entries), 1);
+    assert_eq!(solve_part2(&entries), Ok(1));
   }
 }
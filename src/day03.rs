@@ -1,6 +1,8 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::AocError;
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Cell {
   Empty,
@@ -14,30 +16,42 @@ pub struct Map {
 }
 
 impl Map {
-  pub fn new(input: &str) -> Self {
+  pub fn new(input: &str) -> Result<Self, AocError> {
     let mut cells: Vec<Cell> = vec![];
-    let mut height: usize = 1;
+    let mut height: usize = 0;
     let mut width: usize = 0;
 
-    for character in input.chars() {
-      width += 1;
-
-      match character {
-        '.' => cells.push(Cell::Empty),
-        '#' => cells.push(Cell::Tree),
-        '\n' => {
-          width = 0;
-          height += 1;
+    for (y, line) in input.lines().enumerate() {
+      for (x, character) in line.chars().enumerate() {
+        match character {
+          '.' => cells.push(Cell::Empty),
+          '#' => cells.push(Cell::Tree),
+          _ => {
+            let error_msg = format!("Unexpected character `{}`", character);
+            return Err(AocError::parse(y + 1, x + 1, &error_msg));
+          }
         }
-        _ => {}
       }
+
+      let line_width = line.chars().count();
+      if y == 0 {
+        width = line_width;
+      } else if line_width != width {
+        let error_msg = format!("Expected {} cells, found {}", width, line_width);
+        return Err(AocError::parse(y + 1, 1, &error_msg));
+      }
+      height += 1;
     }
 
-    Map {
-      width: width,
-      height: height,
-      cells: cells,
+    if width == 0 {
+      return Err(AocError::InvalidInput("The map is empty".to_string()));
     }
+
+    Ok(Map {
+      width,
+      height,
+      cells,
+    })
   }
 
   pub fn get_cell(&self, x: usize, y: usize) -> Option<Cell> {
@@ -56,7 +70,7 @@ pub struct SlopeIterator {
 impl SlopeIterator {
   fn new(slope: (usize, usize), map: &Map) -> Self {
     SlopeIterator {
-      slope: slope,
+      slope,
       current: (0, 0),
       limit: (usize::MAX, map.height - 1),
     }
@@ -75,7 +89,7 @@ impl Iterator for SlopeIterator {
     self.current.0 += self.slope.0;
     self.current.1 += self.slope.1;
 
-    return Some(res);
+    Some(res)
   }
 }
 
@@ -93,7 +107,7 @@ fn count_trees_in_map(map: &Map, slope: (usize, usize)) -> u64 {
 }
 
 #[aoc_generator(day3)]
-pub fn parse_input(input: &str) -> Map {
+pub fn parse_input(input: &str) -> Result<Map, AocError> {
   Map::new(input)
 }
 
@@ -119,7 +133,7 @@ mod tests {
   #[test]
   fn test_map_constructor() {
     let input = ".#..\n..#.\n...#";
-    let map = Map::new(input);
+    let map = Map::new(input).unwrap();
 
     assert_eq!(map.width, 4);
     assert_eq!(map.height, 3);
@@ -129,10 +143,23 @@ mod tests {
     assert_eq!(map.cells[11], Cell::Tree);
   }
 
+  #[test]
+  fn test_map_constructor_errors() {
+    assert_eq!(
+      Map::new(".#..\n..x.").err(),
+      Some(AocError::parse(2, 3, "Unexpected character `x`"))
+    );
+    assert_eq!(
+      Map::new(".#..\n..#").err(),
+      Some(AocError::parse(2, 1, "Expected 4 cells, found 3"))
+    );
+    assert!(Map::new("").is_err());
+  }
+
   #[test]
   fn test_map_get_cell() {
     let input = "##..\n..#.\n...#";
-    let map = Map::new(input);
+    let map = Map::new(input).unwrap();
 
     // test getting direct coordinates
     assert_eq!(map.get_cell(0, 0), Some(Cell::Tree));
@@ -158,7 +185,7 @@ mod tests {
                  #.##...#...\n\
                  #...##....#\n\
                  .#..#...#.#";
-    let map = Map::new(input);
+    let map = Map::new(input).unwrap();
     assert_eq!(solve_part1(&map), 7);
   }
 
@@ -175,7 +202,7 @@ mod tests {
                  #.##...#...\n\
                  #...##....#\n\
                  .#..#...#.#";
-    let map = Map::new(input);
+    let map = Map::new(input).unwrap();
     assert_eq!(solve_part2(&map), 336);
   }
 }
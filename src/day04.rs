@@ -1,18 +1,22 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::{blocks, AocError};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 
 type Passport = HashMap<String, String>;
 
-fn parse_passport(input: &str) -> Passport {
+fn parse_passport(input: &str) -> Result<Passport, String> {
   input
     .split_whitespace()
     .map(|pair| {
-      let splitted: Vec<&str> = pair.split(":").collect();
-      (splitted[0].to_string(), splitted[1].to_string())
+      let splitted: Vec<&str> = pair.split(':').collect();
+      match splitted.as_slice() {
+        [field, value] => Ok((field.to_string(), value.to_string())),
+        _ => Err(format!("Invalid field `{}`", pair)),
+      }
     })
     .collect()
 }
@@ -27,12 +31,11 @@ pub fn has_required_fields(passport: &Passport) -> bool {
   let missing: Vec<&String> = required_fields.difference(&passport_fields).collect();
 
   // no missing fields, or only missing "cid"
-  missing.len() == 0 || (missing.len() == 1 && missing[0] == "cid")
+  missing.is_empty() || (missing.len() == 1 && missing[0] == "cid")
 }
 
 fn is_valid_number(value: &str, min: u32, max: u32) -> bool {
-  let x: u32 = value.parse().unwrap();
-  x >= min && x <= max
+  matches!(value.parse::<u32>(), Ok(x) if x >= min && x <= max)
 }
 
 fn is_valid_hex_number(value: &str) -> bool {
@@ -103,25 +106,27 @@ pub fn is_valid_passport(passport: &Passport) -> bool {
     }
   }
 
-  return true;
+  true
 }
 
 #[aoc_generator(day4)]
-pub fn parse_input(input: &str) -> Vec<Passport> {
-  input.split("\n\n").map(parse_passport).collect()
+pub fn parse_input(input: &str) -> Result<Vec<Passport>, AocError> {
+  blocks(input)
+    .map(|(line, block)| parse_passport(block).map_err(|err| AocError::parse(line, 1, &err)))
+    .collect()
 }
 
 #[aoc(day4, part1)]
 pub fn solve_part1(passports: &[Passport]) -> usize {
   passports.iter().fold(0, |total, passport| {
-    total + if has_required_fields(&passport) { 1 } else { 0 }
+    total + if has_required_fields(passport) { 1 } else { 0 }
   })
 }
 
 #[aoc(day4, part2)]
 pub fn solve_part2(passports: &[Passport]) -> usize {
   passports.iter().fold(0, |total, passport| {
-    total + if is_valid_passport(&passport) { 1 } else { 0 }
+    total + if is_valid_passport(passport) { 1 } else { 0 }
   })
 }
 
@@ -132,7 +137,7 @@ mod tests {
   #[test]
   fn test_parse_passport() {
     let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm";
-    let passport = parse_passport(input);
+    let passport = parse_passport(input).unwrap();
     let expected: Passport = [
       (String::from("ecl"), String::from("gry")),
       (String::from("pid"), String::from("860033327")),
@@ -148,6 +153,16 @@ mod tests {
     .collect();
 
     assert_eq!(passport, expected);
+    assert!(parse_passport("ecl:gry pid").is_err());
+  }
+
+  #[test]
+  fn test_parse_input() {
+    let input = "ecl:gry pid:860033327\n\niyr:2013 ecl:amb\ncid:350\n\nhcl:#cfa07d eyr";
+    assert_eq!(
+      parse_input(input),
+      Err(AocError::parse(6, 1, "Invalid field `eyr`"))
+    );
   }
 
   #[test]
@@ -155,22 +170,25 @@ mod tests {
     // test a password with all fields
     let complete = parse_passport(
       "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm",
-    );
+    )
+    .unwrap();
     assert_eq!(has_required_fields(&complete), true);
 
     // test a passport with missing fields
     let incomplete =
-      parse_passport("iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884 hcl:#cfa07d byr:1929");
+      parse_passport("iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884 hcl:#cfa07d byr:1929")
+        .unwrap();
     assert_eq!(has_required_fields(&incomplete), false);
 
     // test a north pole passport
     let complete_northpole =
-      parse_passport("hcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm");
+      parse_passport("hcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm")
+        .unwrap();
     assert_eq!(has_required_fields(&complete_northpole), true);
 
     // test a north pole passport with missing fields
     let incomplete_northpole =
-      parse_passport("hcl:#cfa07d eyr:2025 pid:166559648 iyr:2011 ecl:brn hgt:59in");
+      parse_passport("hcl:#cfa07d eyr:2025 pid:166559648 iyr:2011 ecl:brn hgt:59in").unwrap();
     assert_eq!(has_required_fields(&incomplete_northpole), false);
   }
 
@@ -183,7 +201,7 @@ mod tests {
       "iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719",
     ]
     .iter()
-    .map(|x| parse_passport(x));
+    .map(|x| parse_passport(x).unwrap());
 
     for passport in valid_passports {
       assert_eq!(is_valid_passport(&passport), true)
@@ -196,7 +214,7 @@ mod tests {
       "hgt:59cm ecl:zzz eyr:2038 hcl:74454a iyr:2023 pid:3556412378 byr:2007",
     ]
     .iter()
-    .map(|x| parse_passport(x));
+    .map(|x| parse_passport(x).unwrap());
 
     for passport in invalid_passports {
       assert_eq!(is_valid_passport(&passport), false)
@@ -209,6 +227,7 @@ mod tests {
     assert_eq!(is_valid_height("190cm"), true);
     assert_eq!(is_valid_height("190in"), false);
     assert_eq!(is_valid_height("190"), false);
+    assert_eq!(is_valid_height("99999999999cm"), false);
   }
 
   #[test]
@@ -241,10 +260,13 @@ mod tests {
     let passports = [
       parse_passport(
         "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm",
-      ),
-      parse_passport("iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884 hcl:#cfa07d byr:1929"),
-      parse_passport("hcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm"),
-      parse_passport("hcl:#cfa07d eyr:2025 pid:166559648 iyr:2011 ecl:brn hgt:59in"),
+      )
+      .unwrap(),
+      parse_passport("iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884 hcl:#cfa07d byr:1929")
+        .unwrap(),
+      parse_passport("hcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm")
+        .unwrap(),
+      parse_passport("hcl:#cfa07d eyr:2025 pid:166559648 iyr:2011 ecl:brn hgt:59in").unwrap(),
     ];
 
     assert_eq!(solve_part1(&passports), 2);
@@ -263,7 +285,7 @@ mod tests {
       "hgt:59cm ecl:zzz eyr:2038 hcl:74454a iyr:2023 pid:3556412378 byr:2007",
     ]
     .iter()
-    .map(|x| parse_passport(x))
+    .map(|x| parse_passport(x).unwrap())
     .collect();
 
     assert_eq!(solve_part2(&passports), 4);
@@ -1,6 +1,7 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::AocError;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::ops::Range;
@@ -27,18 +28,28 @@ pub struct BoardingPass {
 }
 
 impl BoardingPass {
-  pub fn new(locator_id: &str) -> Self {
+  // errors point at the first unexpected character, or at the start of the
+  // line if the length is wrong, but always at the first line
+  pub fn new(locator_id: &str) -> Result<Self, AocError> {
     lazy_static! { // use lazy_static to compile the regex only once
-      static ref RE: Regex = Regex::new(r"^(?P<row>\w{7})(?P<col>\w{3})$").unwrap();
+      static ref RE: Regex = Regex::new(r"^(?P<row>[FB]{7})(?P<col>[LR]{3})$").unwrap();
     }
-    let captured = RE.captures(locator_id).unwrap();
+    let captured = RE.captures(locator_id).ok_or_else(|| {
+      let column = locator_id
+        .chars()
+        .enumerate()
+        .position(|(i, c)| !(if i < 7 { "FB" } else { "LR" }).contains(c))
+        .map_or(1, |i| i + 1);
+      let error_msg = format!("Invalid boarding pass `{}`", locator_id);
+      AocError::parse(1, column, &error_msg)
+    })?;
     let row_locator = captured.name("row").unwrap().as_str();
     let col_locator = captured.name("col").unwrap().as_str();
 
-    BoardingPass {
+    Ok(BoardingPass {
       row_locator: row_locator.chars().map(Dir::new).collect(),
       col_locator: col_locator.chars().map(Dir::new).collect(),
-    }
+    })
   }
 
   pub fn get_seat_id(&self) -> usize {
@@ -66,33 +77,37 @@ pub fn locate(range: Range<usize>, locator: &[Dir]) -> usize {
 }
 
 #[aoc_generator(day5)]
-pub fn parse_input(input: &str) -> Vec<BoardingPass> {
-  input.lines().map(BoardingPass::new).collect()
+pub fn parse_input(input: &str) -> Result<Vec<BoardingPass>, AocError> {
+  input
+    .lines()
+    .enumerate()
+    .map(|(i, line)| BoardingPass::new(line).map_err(|err| err.at_line(i + 1)))
+    .collect()
 }
 
 #[aoc(day5, part1)]
-pub fn solve_part1(boarding_passes: &[BoardingPass]) -> usize {
+pub fn solve_part1(boarding_passes: &[BoardingPass]) -> Result<usize, AocError> {
   boarding_passes
     .iter()
     .map(|x| x.get_seat_id())
     .max()
-    .unwrap()
+    .ok_or_else(|| AocError::InvalidInput("There are no boarding passes".to_string()))
 }
 
 #[aoc(day5, part2)]
-pub fn solve_part2(boarding_passes: &[BoardingPass]) -> usize {
+pub fn solve_part2(boarding_passes: &[BoardingPass]) -> Result<usize, AocError> {
   let mut seat_ids: Vec<usize> = boarding_passes.iter().map(|x| x.get_seat_id()).collect();
-  seat_ids.sort();
+  seat_ids.sort_unstable();
 
-  // loop thorugh all the ids, but skip first and last seats
-  for i in 1..seat_ids.len() - 1 {
+  // loop through all the consecutive pairs of ids
+  for pair in seat_ids.windows(2) {
     // detect a gap of a single seat
-    if seat_ids[i - 1] == seat_ids[i] - 2 {
-      return seat_ids[i] - 1 as usize;
+    if pair[0] + 2 == pair[1] {
+      return Ok(pair[0] + 1);
     }
   }
 
-  unreachable!();
+  Err(AocError::NoSolution("There is no free seat".to_string()))
 }
 
 #[cfg(test)]
@@ -102,20 +117,47 @@ mod tests {
 
   #[test]
   fn test_get_seat_id() {
-    assert_eq!(BoardingPass::new("BFFFBBFRRR").get_seat_id(), 567);
-    assert_eq!(BoardingPass::new("FFFBBBFRRR").get_seat_id(), 119);
-    assert_eq!(BoardingPass::new("BBFFBBFRLL").get_seat_id(), 820);
+    assert_eq!(BoardingPass::new("BFFFBBFRRR").unwrap().get_seat_id(), 567);
+    assert_eq!(BoardingPass::new("FFFBBBFRRR").unwrap().get_seat_id(), 119);
+    assert_eq!(BoardingPass::new("BBFFBBFRLL").unwrap().get_seat_id(), 820);
+  }
+
+  #[test]
+  fn test_boarding_pass_new() {
+    assert!(BoardingPass::new("BFFFBBFRRR").is_ok());
+    assert!(BoardingPass::new("BFFFBBFRR").is_err());
+    assert!(BoardingPass::new("BFFFBBFRRX").is_err());
+    assert_eq!(
+      BoardingPass::new("BFFFBBFRRX").err(),
+      Some(AocError::parse(1, 10, "Invalid boarding pass `BFFFBBFRRX`"))
+    );
+    assert_eq!(
+      BoardingPass::new("BFFFBBFRR").err(),
+      Some(AocError::parse(1, 1, "Invalid boarding pass `BFFFBBFRR`"))
+    );
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    let boarding_passes: Vec<BoardingPass> = ["FFFFFFFLLL", "FFFFFFFLLR", "FFFFFFFLRR"]
+      .iter()
+      .map(|x| BoardingPass::new(x).unwrap())
+      .collect();
+
+    assert_eq!(solve_part2(&boarding_passes), Ok(2));
+    assert!(solve_part2(&boarding_passes[..2]).is_err());
   }
 
   #[test]
   fn test_solve_part1() {
     let boarding_passes = [
-      BoardingPass::new("BFFFBBFRRR"),
-      BoardingPass::new("FFFBBBFRRR"),
-      BoardingPass::new("BBFFBBFRLL"),
+      BoardingPass::new("BFFFBBFRRR").unwrap(),
+      BoardingPass::new("FFFBBBFRRR").unwrap(),
+      BoardingPass::new("BBFFBBFRLL").unwrap(),
     ];
 
-    assert_eq!(solve_part1(&boarding_passes), 820);
+    assert_eq!(solve_part1(&boarding_passes), Ok(820));
+    assert!(solve_part1(&[]).is_err());
   }
 
   #[test]
@@ -1,46 +1,66 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
+
+use crate::error::{blocks, AocError};
 use std::collections::HashSet;
 
 type Group = HashSet<char>;
 
-#[aoc_generator(day6, part1)]
-pub fn parse_input_part1(input: &str) -> Vec<Group> {
-  input
-    .split("\n\n")
-    .map(|raw_group| {
-      raw_group
+// the answers of each person in every group, which must be lowercase letters
+fn parse_groups(input: &str) -> Result<Vec<Vec<Group>>, AocError> {
+  blocks(input)
+    .map(|(first_line, raw_group)| {
+      let answers = raw_group
         .lines()
-        .collect::<Vec<&str>>()
-        .concat()
-        .chars()
-        .collect()
+        .enumerate()
+        .map(|(i, person_answers)| {
+          match person_answers.chars().position(|x| !x.is_ascii_lowercase()) {
+            Some(column) => {
+              let error_msg = format!("Invalid answer in `{}`", person_answers);
+              Err(AocError::parse(first_line + i, column + 1, &error_msg))
+            }
+            None => Ok(person_answers.chars().collect::<Group>()),
+          }
+        })
+        .collect::<Result<Vec<Group>, AocError>>()?;
+
+      if answers.is_empty() {
+        return Err(AocError::parse(first_line, 1, "Empty group"));
+      }
+      Ok(answers)
     })
     .collect()
 }
 
+#[aoc_generator(day6, part1)]
+pub fn parse_input_part1(input: &str) -> Result<Vec<Group>, AocError> {
+  let groups = parse_groups(input)?;
+  Ok(
+    groups
+      .iter()
+      .map(|answers| answers.iter().flatten().copied().collect())
+      .collect(),
+  )
+}
+
 #[aoc(day6, part1)]
 pub fn solve_part1(groups: &[Group]) -> usize {
   groups.iter().map(|x| x.len()).sum()
 }
 
 #[aoc_generator(day6, part2)]
-pub fn parse_input_part2(input: &str) -> Vec<Group> {
-  input
-    .split("\n\n")
-    .map(|raw_group| {
-      let answers: Vec<Group> = raw_group
-        .lines()
-        .collect::<Vec<&str>>()
-        .iter()
-        .map(|person_answers| person_answers.chars().collect::<Group>())
-        .collect();
-
-      answers.iter().fold(answers[0].clone(), |result, partial| {
-        result.intersection(&partial).copied().collect::<Group>()
+pub fn parse_input_part2(input: &str) -> Result<Vec<Group>, AocError> {
+  let groups = parse_groups(input)?;
+  Ok(
+    groups
+      .iter()
+      .map(|answers| {
+        answers.iter().fold(answers[0].clone(), |result, partial| {
+          result.intersection(partial).copied().collect::<Group>()
+        })
       })
-    })
-    .collect()
+      .collect(),
+  )
 }
 
 #[aoc(day6, part2)]
@@ -57,14 +77,23 @@ mod tests {
     let input = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb";
     assert_eq!(
       parse_input_part1(input),
-      vec![
+      Ok(vec![
         ['a', 'b', 'c'].iter().cloned().collect::<Group>(),
         ['a', 'b', 'c'].iter().cloned().collect::<Group>(),
         ['a', 'b', 'c'].iter().cloned().collect::<Group>(),
         ['a'].iter().cloned().collect::<Group>(),
         ['b'].iter().cloned().collect::<Group>(),
-      ]
+      ])
+    );
+  }
+
+  #[test]
+  fn test_parse_input_errors() {
+    assert_eq!(
+      parse_input_part1("abc\n\na\nb1"),
+      Err(AocError::parse(4, 2, "Invalid answer in `b1`"))
     );
+    assert!(parse_input_part2("abc\n\n\n\nb").is_err());
   }
 
   #[test]
@@ -85,13 +114,13 @@ mod tests {
     let input = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb";
     assert_eq!(
       parse_input_part2(input),
-      vec![
+      Ok(vec![
         ['a', 'b', 'c'].iter().cloned().collect::<Group>(),
         [].iter().cloned().collect::<Group>(),
         ['a'].iter().cloned().collect::<Group>(),
         ['a'].iter().cloned().collect::<Group>(),
         ['b'].iter().cloned().collect::<Group>(),
-      ]
+      ])
     );
   }
 }
@@ -1,6 +1,7 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::{parse_lines, AocError};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
@@ -42,8 +43,8 @@ fn parse_bag_spec(input: &str) -> Option<(u32, String)> {
     let captured = RE.captures(input)?;
     let amount = captured.name("amount");
     Some((
-      if amount.is_some() {
-        amount.unwrap().as_str().parse().unwrap()
+      if let Some(amount) = amount {
+        amount.as_str().parse().ok()?
       } else {
         0
       },
@@ -55,27 +56,33 @@ fn parse_bag_spec(input: &str) -> Option<(u32, String)> {
 }
 
 #[aoc_generator(day7)]
-pub fn parse_input(input: &str) -> Ruleset {
-  let mut ruleset = Ruleset::new();
-
-  for line in input.lines() {
+pub fn parse_input(input: &str) -> Result<Ruleset, AocError> {
+  let rules = parse_lines(input, |line| {
+    let error_msg = format!("Invalid rule `{}`", line);
     let parts: Vec<&str> = line
-      .trim_end_matches(".")
+      .trim_end_matches('.')
       .split("contain")
       .map(|x| x.trim())
       .collect();
-    let inner: Vec<(u32, String)> = parts[1]
-      .split(", ")
-      .map(parse_bag_spec)
-      .filter(|x| x.is_some())
-      .map(|x| x.unwrap())
-      .collect();
-    let outer = parse_bag_spec(parts[0]).unwrap().1;
-
-    ruleset.insert(outer, inner);
-  }
+    if parts.len() != 2 {
+      return Err(error_msg);
+    }
 
-  ruleset
+    let inner: Vec<(u32, String)> = if parts[1] == "no other bags" {
+      vec![]
+    } else {
+      parts[1]
+        .split(", ")
+        .map(parse_bag_spec)
+        .collect::<Option<_>>()
+        .ok_or(&error_msg)?
+    };
+    let outer = parse_bag_spec(parts[0]).ok_or(&error_msg)?.1;
+
+    Ok((outer, inner))
+  })?;
+
+  Ok(rules.into_iter().collect())
 }
 
 #[aoc(day7, part1)]
@@ -144,7 +151,15 @@ faded blue bags contain no other bags.";
     })
     .collect();
 
-    assert_eq!(parse_input(input), expected_ruleset);
+    assert_eq!(parse_input(input), Ok(expected_ruleset));
+    assert_eq!(
+      parse_input("faded blue bags contain no other bags.\nlight red bags contain blue bags"),
+      Err(AocError::parse(
+        2,
+        1,
+        "Invalid rule `light red bags contain blue bags`"
+      ))
+    );
   }
 
   #[test]
@@ -1,14 +1,51 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
-use std::collections::HashSet;
+
+use crate::error::AocError;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::str::FromStr;
 
+// besides the puzzle's `acc`, `jmp` and `nop`, the handheld understands:
+//   mul N    multiplies the accumulator by N
+//   jz N     jumps like `jmp` if the accumulator is 0
+//   jnz N    jumps like `jmp` if the accumulator isn't 0
+//   out      appends the accumulator to the output
+//   sto rN   copies the accumulator into register N
+//   ld rN    copies register N into the accumulator
+// register instructions keep the register number as their argument, and `out`
+// ignores it
 #[derive(Debug, Clone, PartialEq)]
 pub enum Opcode {
   Accumulate,
   Jump,
   NoOp,
+  Multiply,
+  JumpIfZero,
+  JumpIfNotZero,
+  Output,
+  Store,
+  Load,
+}
+
+pub const REGISTERS: usize = 4;
+
+// how many instructions a program with conditional jumps may run before it's
+// given up on; every state it runs through is kept to detect loops, so the
+// budget bounds memory too
+pub const STEP_LIMIT: usize = 100_000;
+
+impl Opcode {
+  pub fn is_jump(&self) -> bool {
+    matches!(
+      self,
+      Opcode::Jump | Opcode::JumpIfZero | Opcode::JumpIfNotZero
+    )
+  }
+
+  pub fn is_conditional(&self) -> bool {
+    matches!(self, Opcode::JumpIfZero | Opcode::JumpIfNotZero)
+  }
 }
 
 impl FromStr for Opcode {
@@ -18,29 +55,110 @@ impl FromStr for Opcode {
       "acc" => Ok(Opcode::Accumulate),
       "jmp" => Ok(Opcode::Jump),
       "nop" => Ok(Opcode::NoOp),
+      "mul" => Ok(Opcode::Multiply),
+      "jz" => Ok(Opcode::JumpIfZero),
+      "jnz" => Ok(Opcode::JumpIfNotZero),
+      "out" => Ok(Opcode::Output),
+      "sto" => Ok(Opcode::Store),
+      "ld" => Ok(Opcode::Load),
       _ => Err(format!("Unrecognized opcode {}", text).to_string()),
     }
   }
 }
 
+impl fmt::Display for Opcode {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let name = match self {
+      Opcode::Accumulate => "acc",
+      Opcode::Jump => "jmp",
+      Opcode::NoOp => "nop",
+      Opcode::Multiply => "mul",
+      Opcode::JumpIfZero => "jz",
+      Opcode::JumpIfNotZero => "jnz",
+      Opcode::Output => "out",
+      Opcode::Store => "sto",
+      Opcode::Load => "ld",
+    };
+    write!(f, "{}", name)
+  }
+}
+
 pub type Instruction = (Opcode, i64);
 
-#[derive(Debug, PartialEq)]
-enum MachineError {
-  InifiteLoop,
+// an instruction as it's written in the source, with a numeric argument
+pub fn format_instruction((opcode, arg): &Instruction) -> String {
+  match opcode {
+    Opcode::Output => opcode.to_string(),
+    Opcode::Store | Opcode::Load => format!("{} r{}", opcode, arg),
+    _ => format!("{} {:+}", opcode, arg),
+  }
+}
+
+// why a program stopped before reaching its end; `ip` is the instruction that
+// caused it
+#[derive(Debug, Clone, PartialEq)]
+pub enum MachineError {
+  InfiniteLoop { ip: usize },
+  JumpBeforeStart { ip: usize, offset: i64 },
+  JumpPastEnd { ip: usize, offset: i64 },
+  AccumulatorOverflow { ip: usize },
+  InvalidRegister { ip: usize, register: i64 },
+  StepLimit { ip: usize, steps: usize },
+  Halted,
 }
 
 impl fmt::Display for MachineError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{:?}", self)
+    match self {
+      MachineError::InfiniteLoop { ip } => {
+        write!(f, "Infinite loop, instruction #{} would run twice", ip)
+      }
+      MachineError::JumpBeforeStart { ip, offset } => write!(
+        f,
+        "Instruction #{} jumps {} before the start of the program",
+        ip, offset
+      ),
+      MachineError::JumpPastEnd { ip, offset } => write!(
+        f,
+        "Instruction #{} jumps {:+} past the end of the program",
+        ip, offset
+      ),
+      MachineError::AccumulatorOverflow { ip } => {
+        write!(f, "Instruction #{} overflows the accumulator", ip)
+      }
+      MachineError::InvalidRegister { ip, register } => {
+        write!(f, "Instruction #{} uses unknown register r{}", ip, register)
+      }
+      MachineError::StepLimit { ip, steps } => write!(
+        f,
+        "Gave up at instruction #{} after running {} instructions",
+        ip, steps
+      ),
+      MachineError::Halted => write!(f, "The program has already ended"),
+    }
+  }
+}
+
+impl From<MachineError> for AocError {
+  fn from(err: MachineError) -> Self {
+    match err {
+      MachineError::AccumulatorOverflow { .. } => AocError::Overflow(err.to_string()),
+      _ => AocError::InvalidInput(err.to_string()),
+    }
   }
 }
 
-struct Machine {
+type State = (usize, i64, [i64; REGISTERS]);
+
+pub struct Machine {
   program: Vec<Instruction>,
   ip: usize,
   accumulator: i64,
-  ip_run: HashSet<usize>,
+  registers: [i64; REGISTERS],
+  output: Vec<i64>,
+  branches: bool,
+  states_run: HashSet<State>,
+  step_limit: usize,
 }
 
 impl Machine {
@@ -49,85 +167,821 @@ impl Machine {
       program: program.to_vec(),
       ip: 0,
       accumulator: 0,
-      ip_run: HashSet::new(),
+      registers: [0; REGISTERS],
+      output: vec![],
+      branches: program.iter().any(|(opcode, _)| opcode.is_conditional()),
+      states_run: HashSet::new(),
+      step_limit: STEP_LIMIT,
     }
   }
 
+  // only programs with conditional jumps are held to it, the others can't
+  // run more instructions than they have without looping
+  pub fn set_step_limit(&mut self, step_limit: usize) {
+    self.step_limit = step_limit;
+  }
+
+  pub fn ip(&self) -> usize {
+    self.ip
+  }
+
+  pub fn accumulator(&self) -> i64 {
+    self.accumulator
+  }
+
+  pub fn registers(&self) -> &[i64] {
+    &self.registers
+  }
+
+  pub fn output(&self) -> &[i64] {
+    &self.output
+  }
+
+  // whether the program ended by running its last instruction
+  pub fn has_ended(&self) -> bool {
+    self.ip == self.program.len()
+  }
+
+  // jumping exactly to the end of the program is how it terminates
+  fn jump_target(&self, offset: i64) -> Result<usize, MachineError> {
+    let ip = self.ip;
+    match (ip as i64).checked_add(offset) {
+      Some(target) if target < 0 => Err(MachineError::JumpBeforeStart { ip, offset }),
+      Some(target) if target as usize <= self.program.len() => Ok(target as usize),
+      _ => Err(MachineError::JumpPastEnd { ip, offset }),
+    }
+  }
+
+  fn register(&self, register: i64) -> Result<usize, MachineError> {
+    match register {
+      x if (0..REGISTERS as i64).contains(&x) => Ok(x as usize),
+      _ => Err(MachineError::InvalidRegister {
+        ip: self.ip,
+        register,
+      }),
+    }
+  }
+
+  // on error the machine is left as it was before the instruction
   pub fn step(&mut self) -> Result<(), MachineError> {
-    let (opcode, arg) = &self.program[self.ip];
+    let ip = self.ip;
+    let (opcode, arg) = self.program.get(ip).ok_or(MachineError::Halted)?;
+    let arg = *arg;
+    let overflow = MachineError::AccumulatorOverflow { ip };
+    let mut next = ip + 1;
     match opcode {
       Opcode::Accumulate => {
-        self.accumulator += arg;
-        self.ip += 1;
+        self.accumulator = self.accumulator.checked_add(arg).ok_or(overflow)?;
+      }
+      Opcode::Multiply => {
+        self.accumulator = self.accumulator.checked_mul(arg).ok_or(overflow)?;
       }
-      Opcode::NoOp => self.ip += 1,
-      Opcode::Jump => self.ip += *arg as usize,
+      Opcode::NoOp => {}
+      Opcode::Jump => next = self.jump_target(arg)?,
+      Opcode::JumpIfZero if self.accumulator == 0 => next = self.jump_target(arg)?,
+      Opcode::JumpIfNotZero if self.accumulator != 0 => next = self.jump_target(arg)?,
+      Opcode::JumpIfZero | Opcode::JumpIfNotZero => {}
+      Opcode::Output => self.output.push(self.accumulator),
+      Opcode::Store => self.registers[self.register(arg)?] = self.accumulator,
+      Opcode::Load => self.accumulator = self.registers[self.register(arg)?],
     };
+    self.ip = next;
+
+    Ok(())
+  }
+
+  // without conditional jumps, where a program goes doesn't depend on its
+  // data, so running an instruction twice means it would loop forever; with
+  // them, that's only certain when the whole state repeats
+  fn state(&self) -> State {
+    match self.branches {
+      true => (self.ip, self.accumulator, self.registers),
+      false => (self.ip, 0, [0; REGISTERS]),
+    }
+  }
+
+  // like `step`, but refuses to run into an infinite loop, or past the step
+  // limit
+  fn step_once(&mut self) -> Result<(), MachineError> {
+    let state = self.state();
+    if self.states_run.contains(&state) {
+      return Err(MachineError::InfiniteLoop { ip: self.ip });
+    }
+    if self.branches && self.states_run.len() >= self.step_limit {
+      return Err(MachineError::StepLimit {
+        ip: self.ip,
+        steps: self.states_run.len(),
+      });
+    }
+    self.step()?;
+    self.states_run.insert(state);
 
     Ok(())
   }
 
   pub fn run(&mut self) -> Result<(), MachineError> {
-    // loop thorugh program until the end
+    // loop through program until the end
     while self.ip < self.program.len() {
-      // return error if we detect a loop in the program
-      if self.ip_run.contains(&self.ip) {
-        return Err(MachineError::InifiteLoop);
-      }
-      self.ip_run.insert(self.ip);
-      self.step()?;
+      self.step_once()?;
     }
 
     Ok(())
   }
 }
 
-#[aoc_generator(day8)]
-pub fn parse_input(input: &str) -> Vec<Instruction> {
-  input
-    .lines()
-    .map(|line| {
-      let mut tokens = line.split(" ");
-      let opcode = Opcode::from_str(tokens.next().unwrap()).unwrap();
-      let arg = tokens.next().unwrap().parse().unwrap();
-
-      (opcode, arg)
+// a condition to pause a debugged program on, checked after every step
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+  // the instruction at this index is about to run
+  Instruction(usize),
+  AccumulatorEquals(i64),
+  AccumulatorAbove(i64),
+  AccumulatorBelow(i64),
+}
+
+impl Breakpoint {
+  pub fn is_hit(&self, machine: &Machine) -> bool {
+    match *self {
+      Breakpoint::Instruction(ip) => machine.ip == ip,
+      Breakpoint::AccumulatorEquals(x) => machine.accumulator == x,
+      Breakpoint::AccumulatorAbove(x) => machine.accumulator > x,
+      Breakpoint::AccumulatorBelow(x) => machine.accumulator < x,
+    }
+  }
+}
+
+// either an instruction index, like `4`, or a condition on the accumulator,
+// like `acc>10`, `acc<0` or `acc=5`
+impl FromStr for Breakpoint {
+  type Err = String;
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let error_msg = format!("Invalid breakpoint `{}`", text);
+    let number = |x: &str| x.trim().parse::<i64>().map_err(|_| error_msg.clone());
+
+    if let Some(condition) = text.strip_prefix("acc") {
+      let condition = condition.trim_start();
+      let breakpoint = match condition.chars().next() {
+        Some('=') => Breakpoint::AccumulatorEquals(number(&condition[1..])?),
+        Some('>') => Breakpoint::AccumulatorAbove(number(&condition[1..])?),
+        Some('<') => Breakpoint::AccumulatorBelow(number(&condition[1..])?),
+        _ => return Err(error_msg),
+      };
+      return Ok(breakpoint);
+    }
+
+    text
+      .parse::<usize>()
+      .map(Breakpoint::Instruction)
+      .map_err(|_| error_msg.clone())
+  }
+}
+
+impl fmt::Display for Breakpoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Breakpoint::Instruction(ip) => write!(f, "{}", ip),
+      Breakpoint::AccumulatorEquals(x) => write!(f, "acc={}", x),
+      Breakpoint::AccumulatorAbove(x) => write!(f, "acc>{}", x),
+      Breakpoint::AccumulatorBelow(x) => write!(f, "acc<{}", x),
+    }
+  }
+}
+
+// an instruction that has run, with the accumulator right after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+  pub ip: usize,
+  pub opcode: Opcode,
+  pub arg: i64,
+  pub accumulator: i64,
+}
+
+impl fmt::Display for TraceEntry {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "#{:<4} {:<10} acc = {}",
+      self.ip,
+      format_instruction(&(self.opcode.clone(), self.arg)),
+      self.accumulator
+    )
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pause {
+  Breakpoint(Breakpoint),
+  Ended,
+}
+
+// runs a program step by step, pausing on breakpoints and keeping a trace of
+// every instruction run; like `Machine::run`, it fails instead of running an
+// instruction for a second time
+pub struct Debugger {
+  machine: Machine,
+  breakpoints: Vec<Breakpoint>,
+  trace: Vec<TraceEntry>,
+}
+
+impl Debugger {
+  pub fn new(program: &[Instruction]) -> Self {
+    Debugger {
+      machine: Machine::new(program),
+      breakpoints: vec![],
+      trace: vec![],
+    }
+  }
+
+  pub fn machine(&self) -> &Machine {
+    &self.machine
+  }
+
+  pub fn program(&self) -> &[Instruction] {
+    &self.machine.program
+  }
+
+  pub fn trace(&self) -> &[TraceEntry] {
+    &self.trace
+  }
+
+  pub fn breakpoints(&self) -> &[Breakpoint] {
+    &self.breakpoints
+  }
+
+  pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+    if !self.breakpoints.contains(&breakpoint) {
+      self.breakpoints.push(breakpoint);
+    }
+  }
+
+  // returns whether there was such a breakpoint
+  pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+    let len = self.breakpoints.len();
+    self.breakpoints.retain(|x| x != breakpoint);
+    self.breakpoints.len() != len
+  }
+
+  pub fn step(&mut self) -> Result<&TraceEntry, MachineError> {
+    let ip = self.machine.ip;
+    let (opcode, arg) = self
+      .machine
+      .program
+      .get(ip)
+      .cloned()
+      .ok_or(MachineError::Halted)?;
+    self.machine.step_once()?;
+    self.trace.push(TraceEntry {
+      ip,
+      opcode,
+      arg,
+      accumulator: self.machine.accumulator,
+    });
+
+    Ok(&self.trace[self.trace.len() - 1])
+  }
+
+  // runs at least one instruction, then keeps going until a breakpoint is hit
+  // or the program ends
+  pub fn run_until_break(&mut self) -> Result<Pause, MachineError> {
+    loop {
+      self.step()?;
+      if self.machine.has_ended() {
+        return Ok(Pause::Ended);
+      }
+      let machine = &self.machine;
+      if let Some(breakpoint) = self.breakpoints.iter().find(|x| x.is_hit(machine)) {
+        return Ok(Pause::Breakpoint(breakpoint.clone()));
+      }
+    }
+  }
+}
+
+// the words of a line, with their 1-based columns
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+  let mut tokens = vec![];
+  let mut start = None;
+  for (i, c) in line.char_indices().chain(Some((line.len(), ' '))) {
+    match (start, c.is_whitespace()) {
+      (None, false) => start = Some(i),
+      (Some(word_start), true) => {
+        tokens.push((word_start + 1, &line[word_start..i]));
+        start = None;
+      }
+      _ => {}
+    }
+  }
+
+  tokens
+}
+
+fn is_label(text: &str) -> bool {
+  let mut chars = text.chars();
+  chars
+    .next()
+    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_register(text: &str) -> Option<i64> {
+  text
+    .strip_prefix('r')
+    .and_then(|x| x.parse::<usize>().ok())
+    .filter(|&x| x < REGISTERS)
+    .map(|x| x as i64)
+}
+
+// errors are reported at line 1, and moved to the right line by `assemble`
+fn assemble_instruction(
+  ip: usize,
+  code: &str,
+  tokens: &[(usize, &str)],
+  labels: &HashMap<&str, usize>,
+) -> Result<Instruction, AocError> {
+  let (column, mnemonic) = tokens[0];
+  let opcode = Opcode::from_str(mnemonic).map_err(|err| AocError::parse(1, column, &err))?;
+  let error = |column: usize, message: String| AocError::parse(1, column, &message);
+
+  let arg = match (&opcode, &tokens[1..]) {
+    (Opcode::Output, []) => 0,
+    (Opcode::Store | Opcode::Load, [(column, register)]) => parse_register(register)
+      .ok_or_else(|| error(*column, format!("Unknown register `{}`", register)))?,
+    (Opcode::Accumulate | Opcode::Multiply, [(column, number)]) => number
+      .parse()
+      .map_err(|_| error(*column, format!("Invalid number `{}`", number)))?,
+    (_, [(column, target)]) if opcode.is_jump() || opcode == Opcode::NoOp => {
+      match labels.get(target) {
+        Some(&index) => index as i64 - ip as i64,
+        None if is_label(target) => {
+          return Err(error(*column, format!("Unknown label `{}`", target)))
+        }
+        None => target
+          .parse()
+          .map_err(|_| error(*column, format!("Invalid offset `{}`", target)))?,
+      }
+    }
+    _ => return Err(error(1, format!("Invalid instruction `{}`", code))),
+  };
+
+  Ok((opcode, arg))
+}
+
+// the source has an instruction per line, optionally after a `label:`, and
+// comments from `#` or `;` to the end of the line; jumps and nops take either
+// an offset or a label, which may also be at the end of the program
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, AocError> {
+  let mut labels: HashMap<&str, usize> = HashMap::new();
+  let mut lines = vec![];
+
+  for (i, line) in source.lines().enumerate() {
+    let code = line.split(['#', ';']).next().unwrap_or("");
+    let mut tokens = tokenize(code);
+    if let Some(&(column, token)) = tokens.first() {
+      if let Some(label) = token.strip_suffix(':') {
+        if !is_label(label) {
+          let error_msg = format!("Invalid label `{}`", label);
+          return Err(AocError::parse(i + 1, column, &error_msg));
+        }
+        if labels.insert(label, lines.len()).is_some() {
+          let error_msg = format!("Duplicated label `{}`", label);
+          return Err(AocError::parse(i + 1, column, &error_msg));
+        }
+        tokens.remove(0);
+      }
+    }
+    if !tokens.is_empty() {
+      lines.push((i + 1, code.trim(), tokens));
+    }
+  }
+
+  lines
+    .iter()
+    .enumerate()
+    .map(|(ip, (line, code, tokens))| {
+      assemble_instruction(ip, code, tokens, &labels).map_err(|err| err.at_line(*line))
     })
     .collect()
 }
 
+// prints a program back as source, with a label on every jump target, so
+// that assembling it gives the same program back
+pub fn disassemble(program: &[Instruction]) -> String {
+  let label = |target: usize| format!("L{}", target);
+  let targets: BTreeSet<usize> = program
+    .iter()
+    .enumerate()
+    .filter(|(_, (opcode, _))| opcode.is_jump())
+    .filter_map(|(ip, (_, arg))| jump_target(program, ip, *arg))
+    .collect();
+
+  let mut lines = vec![];
+  for (ip, instruction) in program.iter().enumerate() {
+    if targets.contains(&ip) {
+      lines.push(format!("{}:", label(ip)));
+    }
+    let (opcode, arg) = instruction;
+    lines.push(match jump_target(program, ip, *arg) {
+      Some(target) if opcode.is_jump() => format!("  {} {}", opcode, label(target)),
+      _ => format!("  {}", format_instruction(instruction)),
+    });
+  }
+  if targets.contains(&program.len()) {
+    lines.push(format!("{}:", label(program.len())));
+  }
+
+  lines.join("\n")
+}
+
+// puzzle inputs are plain programs, without labels or comments
+#[aoc_generator(day8)]
+pub fn parse_input(input: &str) -> Result<Vec<Instruction>, AocError> {
+  assemble(input)
+}
+
+// the accumulator right before any instruction runs twice
 #[aoc(day8, part1)]
-pub fn solve_part1(program: &[Instruction]) -> i64 {
+pub fn solve_part1(program: &[Instruction]) -> Result<i64, AocError> {
   let mut machine = Machine::new(program);
-  let _ = machine.run();
-  machine.accumulator
+  match machine.run() {
+    Ok(()) | Err(MachineError::InfiniteLoop { .. }) => Ok(machine.accumulator),
+    Err(err) => Err(err.into()),
+  }
 }
 
-#[aoc(day8, part2)]
-pub fn solve_part2(program: &[Instruction]) -> Result<i64, String> {
-  for (i, (opcode, arg)) in program.iter().enumerate() {
-    let instruction = match opcode {
-      Opcode::NoOp => Some((Opcode::Jump, *arg)),
-      Opcode::Jump => Some((Opcode::NoOp, *arg)),
-      _ => None,
+// the nop or jmp swapped to make a program terminate, with the accumulator
+// at the end of the patched program
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repair {
+  pub index: usize,
+  pub patch: Instruction,
+  pub accumulator: i64,
+}
+
+fn swapped(instruction: &Instruction) -> Option<Instruction> {
+  match instruction {
+    (Opcode::NoOp, arg) => Some((Opcode::Jump, *arg)),
+    (Opcode::Jump, arg) => Some((Opcode::NoOp, *arg)),
+    _ => None,
+  }
+}
+
+// where a jump at `ip` goes, or none if it's out of the program;
+// `program.len()` means the program ended
+pub fn jump_target(program: &[Instruction], ip: usize, offset: i64) -> Option<usize> {
+  (ip as i64)
+    .checked_add(offset)
+    .filter(|&target| target >= 0 && target as usize <= program.len())
+    .map(|target| target as usize)
+}
+
+// where execution may go after running `instruction` at `ip`, leaving out
+// jumps out of the program; only conditional jumps have two successors
+pub fn successors(program: &[Instruction], ip: usize, instruction: &Instruction) -> Vec<usize> {
+  let (opcode, arg) = instruction;
+  let jump = jump_target(program, ip, *arg);
+  match opcode {
+    Opcode::Jump => jump.into_iter().collect(),
+    _ if opcode.is_conditional() => Some(ip + 1).into_iter().chain(jump).collect(),
+    _ => vec![ip + 1],
+  }
+}
+
+// which instructions may lead to the end of the program, unpatched; walking
+// the control-flow graph backwards from the end visits each of them at most
+// once; without conditional jumps, those instructions are certain to end it
+pub fn terminating(program: &[Instruction]) -> Vec<bool> {
+  let mut predecessors: Vec<Vec<usize>> = vec![vec![]; program.len() + 1];
+  for (ip, instruction) in program.iter().enumerate() {
+    for next in successors(program, ip, instruction) {
+      predecessors[next].push(ip);
+    }
+  }
+
+  let mut terminating = vec![false; program.len() + 1];
+  let mut pending = vec![program.len()];
+  terminating[program.len()] = true;
+  while let Some(ip) = pending.pop() {
+    for &previous in predecessors[ip].iter() {
+      if !terminating[previous] {
+        terminating[previous] = true;
+        pending.push(previous);
+      }
+    }
+  }
+
+  terminating
+}
+
+// a patched program that runs out of steps may still end, so that's the only
+// error reported instead of taken as not ending
+fn run_patched(
+  program: &[Instruction],
+  index: usize,
+  patch: Instruction,
+) -> Result<Option<Repair>, MachineError> {
+  let mut patched = program.to_vec();
+  patched[index] = patch.clone();
+  let mut machine = Machine::new(&patched);
+  match machine.run() {
+    Ok(()) => Ok(Some(Repair {
+      index,
+      patch,
+      accumulator: machine.accumulator,
+    })),
+    Err(err @ MachineError::StepLimit { .. }) => Err(err),
+    Err(_) => Ok(None),
+  }
+}
+
+// only the instructions the unpatched program runs can change where it goes,
+// and swapping one of them fixes it if it then continues into an instruction
+// that leads to the end; that instruction can't lead back to the swapped one,
+// or the unpatched program would have ended too, so it's enough to run the
+// patched program once to get the accumulator; that reasoning needs control
+// flow not to depend on data, so programs with conditional jumps are left to
+// the brute force; a program that ends unpatched needs no repair, and is
+// reported as having no solution
+pub fn repair(program: &[Instruction]) -> Result<Repair, AocError> {
+  if program.iter().any(|(opcode, _)| opcode.is_conditional()) {
+    return repair_brute_force(program);
+  }
+
+  let terminating = terminating(program);
+  if terminating[0] {
+    return Err(no_repair_needed());
+  }
+  let mut visited = vec![false; program.len()];
+  let mut ip = 0;
+
+  while ip < program.len() && !visited[ip] {
+    visited[ip] = true;
+    if let Some(patch) = swapped(&program[ip]) {
+      let lands_on_end = successors(program, ip, &patch)
+        .iter()
+        .any(|&x| terminating[x]);
+      if lands_on_end {
+        // the run can still fail if the accumulator overflows
+        if let Some(repair) = run_patched(program, ip, patch)? {
+          return Ok(repair);
+        }
+      }
+    }
+    ip = match successors(program, ip, &program[ip]).as_slice() {
+      [next] => *next,
+      _ => break,
     };
+  }
+
+  Err(AocError::NoSolution(
+    "Could not patch program to fix infinite loop.".to_string(),
+  ))
+}
 
-    if let Some(patch) = instruction {
-      let mut machine = Machine::new(&[&program[..i], &[patch], &program[i + 1..]].concat());
-      let result = machine.run();
-      if result.is_ok() {
-        return Ok(machine.accumulator);
+fn no_repair_needed() -> AocError {
+  AocError::NoSolution("Program already terminates, no repair needed.".to_string())
+}
+
+// tries every swap in program order, running the whole program for each one;
+// kept as a reference for `repair`
+pub fn repair_brute_force(program: &[Instruction]) -> Result<Repair, AocError> {
+  match Machine::new(program).run() {
+    Ok(()) => return Err(no_repair_needed()),
+    Err(err @ MachineError::StepLimit { .. }) => return Err(err.into()),
+    Err(_) => {}
+  }
+
+  for (i, instruction) in program.iter().enumerate() {
+    if let Some(patch) = swapped(instruction) {
+      if let Some(repair) = run_patched(program, i, patch)? {
+        return Ok(repair);
+      }
+    }
+  }
+
+  Err(AocError::NoSolution(
+    "Could not patch program to fix infinite loop.".to_string(),
+  ))
+}
+
+#[aoc(day8, part2)]
+pub fn solve_part2(program: &[Instruction]) -> Result<i64, AocError> {
+  repair(program).map(|x| x.accumulator)
+}
+
+// the control-flow graph, leaving out the edges to the end of the program
+fn control_flow(program: &[Instruction]) -> Vec<Vec<usize>> {
+  program
+    .iter()
+    .enumerate()
+    .map(|(ip, instruction)| {
+      let mut edges = successors(program, ip, instruction);
+      edges.retain(|&x| x < program.len());
+      edges
+    })
+    .collect()
+}
+
+// instructions that no path from the start of the program gets to
+pub fn unreachable(program: &[Instruction]) -> Vec<usize> {
+  let edges = control_flow(program);
+  let mut reached = vec![false; program.len()];
+  let mut pending = vec![];
+  if !program.is_empty() {
+    reached[0] = true;
+    pending.push(0);
+  }
+  while let Some(ip) = pending.pop() {
+    for &next in edges[ip].iter() {
+      if !reached[next] {
+        reached[next] = true;
+        pending.push(next);
       }
     }
   }
 
-  Err("Could not patch program to fix infinite loop.".to_string())
+  (0..program.len()).filter(|&ip| !reached[ip]).collect()
+}
+
+// the strongly connected components of the control-flow graph that can run
+// their instructions more than once, sorted; this is Tarjan's algorithm with
+// an explicit stack, so that long programs can't overflow the call stack
+pub fn loops(program: &[Instruction]) -> Vec<Vec<usize>> {
+  let edges = control_flow(program);
+  let mut index: Vec<Option<usize>> = vec![None; program.len()];
+  let mut lowlink = vec![0; program.len()];
+  let mut on_stack = vec![false; program.len()];
+  let mut stack = vec![];
+  let mut counter = 0;
+  let mut components = vec![];
+
+  for root in 0..program.len() {
+    if index[root].is_some() {
+      continue;
+    }
+
+    // each entry is an instruction and the next of its edges to follow
+    let mut work = vec![(root, 0)];
+    while let Some(&(ip, edge)) = work.last() {
+      if index[ip].is_none() {
+        index[ip] = Some(counter);
+        lowlink[ip] = counter;
+        counter += 1;
+        stack.push(ip);
+        on_stack[ip] = true;
+      }
+
+      if let Some(&next) = edges[ip].get(edge) {
+        work.last_mut().unwrap().1 += 1;
+        match index[next] {
+          None => work.push((next, 0)),
+          Some(next_index) if on_stack[next] => lowlink[ip] = lowlink[ip].min(next_index),
+          _ => {}
+        }
+        continue;
+      }
+
+      work.pop();
+      if let Some(&(parent, _)) = work.last() {
+        lowlink[parent] = lowlink[parent].min(lowlink[ip]);
+      }
+      if index[ip] == Some(lowlink[ip]) {
+        let mut component = vec![];
+        while let Some(x) = stack.pop() {
+          on_stack[x] = false;
+          component.push(x);
+          if x == ip {
+            break;
+          }
+        }
+        if component.len() > 1 || edges[ip].contains(&ip) {
+          component.sort_unstable();
+          components.push(component);
+        }
+      }
+    }
+  }
+
+  components.sort();
+  components
+}
+
+// every single swap that makes a program end; unlike `repair`, it runs the
+// patched program for every swap, since it has to find all of them
+pub fn terminating_patches(program: &[Instruction]) -> Result<Vec<Repair>, MachineError> {
+  let mut patches = vec![];
+  for (i, instruction) in program.iter().enumerate() {
+    if let Some(patch) = swapped(instruction) {
+      patches.extend(run_patched(program, i, patch)?);
+    }
+  }
+
+  Ok(patches)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+  pub length: usize,
+  // how the unpatched program stops, and its accumulator at that point
+  pub result: Result<(), MachineError>,
+  pub accumulator: i64,
+  pub unreachable: Vec<usize>,
+  pub loops: Vec<Vec<usize>>,
+  pub patches: Result<Vec<Repair>, MachineError>,
+}
+
+pub fn analyze(program: &[Instruction]) -> Report {
+  let mut machine = Machine::new(program);
+  let result = machine.run();
+
+  Report {
+    length: program.len(),
+    result,
+    accumulator: machine.accumulator,
+    unreachable: unreachable(program),
+    loops: loops(program),
+    patches: terminating_patches(program),
+  }
+}
+
+// sorted instruction indices, with consecutive ones as ranges
+fn format_indices(indices: &[usize]) -> String {
+  let mut ranges: Vec<(usize, usize)> = vec![];
+  for &ip in indices {
+    match ranges.last_mut() {
+      Some((_, end)) if *end + 1 == ip => *end = ip,
+      _ => ranges.push((ip, ip)),
+    }
+  }
+
+  let ranges: Vec<String> = ranges
+    .iter()
+    .map(|&(start, end)| match start == end {
+      true => format!("#{}", start),
+      false => format!("#{}-#{}", start, end),
+    })
+    .collect();
+  ranges.join(", ")
+}
+
+impl fmt::Display for Report {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut lines = vec![format!("Instructions: {}", self.length)];
+    lines.push(match &self.result {
+      Ok(()) => format!("Unpatched: ends with acc = {}", self.accumulator),
+      Err(err) => format!("Unpatched: {} (acc = {})", err, self.accumulator),
+    });
+
+    if self.unreachable.is_empty() {
+      lines.push("Unreachable: none".to_string());
+    } else {
+      lines.push(format!(
+        "Unreachable: {}",
+        format_indices(&self.unreachable)
+      ));
+    }
+
+    if self.loops.is_empty() {
+      lines.push("Loops: none".to_string());
+    } else {
+      lines.push("Loops:".to_string());
+      for component in self.loops.iter() {
+        lines.push(format!("  {}", format_indices(component)));
+      }
+    }
+
+    match &self.patches {
+      Err(err) => lines.push(format!("Terminating patches: {}", err)),
+      Ok(patches) if patches.is_empty() => lines.push("Terminating patches: none".to_string()),
+      Ok(patches) => {
+        lines.push("Terminating patches:".to_string());
+        for patch in patches.iter() {
+          lines.push(format!(
+            "  #{} {}: acc = {}",
+            patch.index,
+            format_instruction(&patch.patch),
+            patch.accumulator
+          ));
+        }
+      }
+    }
+
+    write!(f, "{}", lines.join("\n"))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  const EXAMPLE: &str = "nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
   #[test]
   fn test_opcode_from_str() {
     assert_eq!(Opcode::from_str("acc"), Ok(Opcode::Accumulate));
@@ -151,10 +1005,47 @@ mod tests {
     assert_eq!(machine_nop.accumulator, 0);
     assert_eq!(machine_nop.ip, 1);
 
-    let mut machine_jmp = Machine::new(&[(Opcode::Jump, 12)]);
+    let mut machine_jmp = Machine::new(&[(Opcode::Jump, 2), (Opcode::NoOp, 0)]);
     let _ = machine_jmp.step();
     assert_eq!(machine_jmp.accumulator, 0);
-    assert_eq!(machine_jmp.ip, 12);
+    assert_eq!(machine_jmp.ip, 2);
+    assert!(machine_jmp.has_ended());
+    assert_eq!(machine_jmp.step(), Err(MachineError::Halted));
+  }
+
+  #[test]
+  fn test_step_errors() {
+    let program = [(Opcode::NoOp, 0), (Opcode::Jump, -2), (Opcode::Jump, 2)];
+    let mut machine = Machine::new(&program);
+    machine.ip = 1;
+    assert_eq!(
+      machine.step(),
+      Err(MachineError::JumpBeforeStart { ip: 1, offset: -2 })
+    );
+    assert_eq!(machine.ip, 1);
+
+    machine.ip = 2;
+    assert_eq!(
+      machine.step(),
+      Err(MachineError::JumpPastEnd { ip: 2, offset: 2 })
+    );
+
+    let mut machine = Machine::new(&[(Opcode::Jump, i64::MAX)]);
+    assert_eq!(
+      machine.step(),
+      Err(MachineError::JumpPastEnd {
+        ip: 0,
+        offset: i64::MAX
+      })
+    );
+
+    let mut machine = Machine::new(&[(Opcode::Accumulate, i64::MAX), (Opcode::Accumulate, 1)]);
+    assert_eq!(machine.step(), Ok(()));
+    assert_eq!(
+      machine.step(),
+      Err(MachineError::AccumulatorOverflow { ip: 1 })
+    );
+    assert_eq!(machine.accumulator, i64::MAX);
   }
 
   #[test]
@@ -172,23 +1063,374 @@ mod tests {
     let result = machine.run();
     assert_eq!(machine.ip, 0);
     assert_eq!(machine.accumulator, 0);
-    assert_eq!(result.unwrap_err(), MachineError::InifiteLoop);
+    assert_eq!(result.unwrap_err(), MachineError::InfiniteLoop { ip: 0 });
+  }
+
+  #[test]
+  fn test_breakpoint_from_str() {
+    assert_eq!("4".parse(), Ok(Breakpoint::Instruction(4)));
+    assert_eq!("acc=5".parse(), Ok(Breakpoint::AccumulatorEquals(5)));
+    assert_eq!("acc > -3".parse(), Ok(Breakpoint::AccumulatorAbove(-3)));
+    assert_eq!("acc<0".parse(), Ok(Breakpoint::AccumulatorBelow(0)));
+    assert_eq!(
+      "acc!0".parse::<Breakpoint>(),
+      Err("Invalid breakpoint `acc!0`".to_string())
+    );
+    assert!("-1".parse::<Breakpoint>().is_err());
+    assert_eq!(Breakpoint::AccumulatorAbove(-3).to_string(), "acc>-3");
+  }
+
+  #[test]
+  fn test_debugger_step() {
+    let program = parse_input(EXAMPLE).unwrap();
+    let mut debugger = Debugger::new(&program);
+    debugger.step().unwrap();
+    let entry = debugger.step().unwrap().clone();
+    assert_eq!(
+      entry,
+      TraceEntry {
+        ip: 1,
+        opcode: Opcode::Accumulate,
+        arg: 1,
+        accumulator: 1
+      }
+    );
+    assert_eq!(entry.to_string(), "#1    acc +1     acc = 1");
+    assert_eq!(debugger.machine().ip(), 2);
+    assert_eq!(debugger.trace().len(), 2);
+  }
+
+  #[test]
+  fn test_debugger_run_until_break() {
+    let program = parse_input(EXAMPLE).unwrap();
+    let mut debugger = Debugger::new(&program);
+    debugger.add_breakpoint(Breakpoint::Instruction(4));
+    debugger.add_breakpoint(Breakpoint::AccumulatorAbove(4));
+
+    assert_eq!(
+      debugger.run_until_break(),
+      Ok(Pause::Breakpoint(Breakpoint::Instruction(4)))
+    );
+    assert_eq!(debugger.machine().accumulator(), 5);
+    assert_eq!(
+      debugger.run_until_break(),
+      Ok(Pause::Breakpoint(Breakpoint::AccumulatorAbove(4)))
+    );
+    assert_eq!(debugger.machine().ip(), 1);
+    assert!(debugger.remove_breakpoint(&Breakpoint::Instruction(4)));
+    assert!(!debugger.remove_breakpoint(&Breakpoint::Instruction(4)));
+    assert_eq!(
+      debugger.run_until_break(),
+      Err(MachineError::InfiniteLoop { ip: 1 })
+    );
+    let ips: Vec<usize> = debugger.trace().iter().map(|x| x.ip).collect();
+    assert_eq!(ips, vec![0, 1, 2, 6, 7, 3, 4]);
+  }
+
+  #[test]
+  fn test_debugger_ended() {
+    let mut debugger = Debugger::new(&[(Opcode::Accumulate, 3), (Opcode::Jump, 1)]);
+    debugger.add_breakpoint(Breakpoint::AccumulatorEquals(0));
+    assert_eq!(debugger.run_until_break(), Ok(Pause::Ended));
+    assert_eq!(debugger.machine().accumulator(), 3);
+    assert_eq!(debugger.step(), Err(MachineError::Halted));
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    let program = parse_input(EXAMPLE).unwrap();
+    assert_eq!(solve_part1(&program), Ok(5));
+    assert_eq!(
+      solve_part1(&[(Opcode::Jump, -1)]),
+      Err(AocError::InvalidInput(
+        "Instruction #0 jumps -1 before the start of the program".to_string()
+      ))
+    );
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    let program = parse_input(EXAMPLE).unwrap();
+    assert_eq!(solve_part2(&program), Ok(8));
+  }
+
+  #[test]
+  fn test_terminating() {
+    let program = parse_input(EXAMPLE).unwrap();
+    let expected = [
+      false, false, false, false, false, false, false, false, true, true,
+    ];
+    assert_eq!(terminating(&program), expected);
+    assert_eq!(
+      terminating(&[(Opcode::Jump, 2), (Opcode::Jump, -5)]),
+      vec![true, false, true]
+    );
+  }
+
+  #[test]
+  fn test_repair() {
+    let program = parse_input(EXAMPLE).unwrap();
+    let expected = Repair {
+      index: 7,
+      patch: (Opcode::NoOp, -4),
+      accumulator: 8,
+    };
+    assert_eq!(repair(&program), Ok(expected.clone()));
+    assert_eq!(repair_brute_force(&program), Ok(expected));
+    assert!(repair(&[(Opcode::Jump, 0), (Opcode::Jump, -1)]).is_err());
+  }
+
+  #[test]
+  fn test_repair_not_needed() {
+    let program = [
+      (Opcode::NoOp, 0),
+      (Opcode::Accumulate, 1),
+      (Opcode::Jump, 1),
+    ];
+    assert_eq!(repair(&program), Err(no_repair_needed()));
+    assert_eq!(repair_brute_force(&program), Err(no_repair_needed()));
+    assert_eq!(repair(&[]), Err(no_repair_needed()));
+  }
+
+  #[test]
+  fn test_repair_matches_brute_force() {
+    // pseudo-random programs from a linear congruential generator; most
+    // of them loop, but some end or jump out of the program unpatched
+    let mut seed: u64 = 2020;
+    let mut random = |n: u64| {
+      seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+      (seed >> 33) % n
+    };
+
+    let mut repaired = 0;
+    for _ in 0..500 {
+      let len = 1 + random(12) as i64;
+      let program: Vec<Instruction> = (0..len)
+        .map(|_| {
+          let opcode = [Opcode::Accumulate, Opcode::Jump, Opcode::NoOp][random(3) as usize].clone();
+          (opcode, random(2 * len as u64 + 1) as i64 - len)
+        })
+        .collect();
+
+      let expected = repair_brute_force(&program);
+      assert_eq!(repair(&program), expected, "{:?}", program);
+      repaired += expected.is_ok() as usize;
+    }
+    assert!(repaired > 0);
+  }
+
+  #[test]
+  fn test_extended_instructions() {
+    let program =
+      assemble("acc +3\nsto r1\nmul -2\nout\nld r1\njz +2\njnz +2\nacc +99\nout").unwrap();
+    let mut machine = Machine::new(&program);
+    assert_eq!(machine.run(), Ok(()));
+    assert_eq!(machine.output(), [-6, 3]);
+    assert_eq!(machine.registers(), [0, 3, 0, 0]);
+
+    let mut machine = Machine::new(&[(Opcode::Load, 4)]);
+    assert_eq!(
+      machine.step(),
+      Err(MachineError::InvalidRegister { ip: 0, register: 4 })
+    );
+    let mut machine = Machine::new(&[(Opcode::Accumulate, i64::MAX), (Opcode::Multiply, 2)]);
+    machine.step().unwrap();
+    assert_eq!(
+      machine.step(),
+      Err(MachineError::AccumulatorOverflow { ip: 1 })
+    );
+  }
+
+  #[test]
+  fn test_conditional_loops() {
+    // counts down from 3, so the loop runs its instructions several times
+    let source = "
+      acc +3
+      loop: out      ; print the counter
+      acc -1
+      jnz loop
+      jmp end        # skip the next line
+      acc +100
+      end:";
+    let program = assemble(source).unwrap();
+    assert_eq!(program[3], (Opcode::JumpIfNotZero, -2));
+    assert_eq!(program[4], (Opcode::Jump, 2));
+    let mut machine = Machine::new(&program);
+    assert_eq!(machine.run(), Ok(()));
+    assert_eq!(machine.output(), [3, 2, 1]);
+
+    let mut machine = Machine::new(&assemble("loop: jz loop").unwrap());
+    assert_eq!(machine.run(), Err(MachineError::InfiniteLoop { ip: 0 }));
+    assert_eq!(successors(&program, 3, &program[3]), vec![4, 1]);
+    assert_eq!(repair(&program), repair_brute_force(&program));
+  }
+
+  #[test]
+  fn test_step_limit() {
+    // the accumulator never repeats, so no state does either, and swapping
+    // the unreachable nop doesn't change that
+    let program = assemble("acc +1\njnz -1\nnop +0").unwrap();
+    let mut machine = Machine::new(&program);
+    machine.set_step_limit(1000);
+    assert_eq!(
+      machine.run(),
+      Err(MachineError::StepLimit { ip: 0, steps: 1000 })
+    );
+    assert_eq!(machine.accumulator(), 500);
+
+    let limit = MachineError::StepLimit {
+      ip: 0,
+      steps: STEP_LIMIT,
+    };
+    assert_eq!(Machine::new(&program).run(), Err(limit.clone()));
+    assert_eq!(
+      Debugger::new(&program).run_until_break(),
+      Err(limit.clone())
+    );
+    assert_eq!(repair_brute_force(&program), Err(limit.clone().into()));
+    assert_eq!(repair(&program), Err(limit.clone().into()));
+    assert_eq!(terminating_patches(&program), Err(limit.clone()));
+    assert_eq!(analyze(&program).result, Err(limit));
+  }
+
+  #[test]
+  fn test_assemble_errors() {
+    assert_eq!(
+      assemble("acc +1\n  jmp nowhere"),
+      Err(AocError::parse(2, 7, "Unknown label `nowhere`"))
+    );
+    assert_eq!(
+      assemble("a:\nnop +0\na: acc +1"),
+      Err(AocError::parse(3, 1, "Duplicated label `a`"))
+    );
+    assert_eq!(
+      assemble("1a: nop +0"),
+      Err(AocError::parse(1, 1, "Invalid label `1a`"))
+    );
+    assert_eq!(
+      assemble("sto r4"),
+      Err(AocError::parse(1, 5, "Unknown register `r4`"))
+    );
+    assert_eq!(
+      assemble("mul x"),
+      Err(AocError::parse(1, 5, "Invalid number `x`"))
+    );
+    assert_eq!(
+      assemble("jmp +1x"),
+      Err(AocError::parse(1, 5, "Invalid offset `+1x`"))
+    );
+    assert_eq!(
+      assemble("out +1"),
+      Err(AocError::parse(1, 1, "Invalid instruction `out +1`"))
+    );
+  }
+
+  #[test]
+  fn test_disassemble() {
+    let program = parse_input(EXAMPLE).unwrap();
+    let expected = "  nop +0
+L1:
+  acc +1
+  jmp L6
+L3:
+  acc +3
+  jmp L1
+  acc -99
+L6:
+  acc +1
+  jmp L3
+  acc +6";
+    assert_eq!(disassemble(&program), expected);
+    assert_eq!(assemble(expected), Ok(program));
+
+    let program = assemble("sto r2\nld r2\nout\njz +3\njmp -5\nmul +2").unwrap();
+    assert_eq!(
+      disassemble(&program),
+      "  sto r2\n  ld r2\n  out\n  jz L6\n  jmp -5\n  mul +2\nL6:"
+    );
+    assert_eq!(assemble(&disassemble(&program)), Ok(program));
+  }
+
+  #[test]
+  fn test_unreachable() {
+    let program = parse_input(EXAMPLE).unwrap();
+    assert_eq!(unreachable(&program), vec![5, 8]);
+    assert_eq!(unreachable(&assemble("jz +2\nout\nout").unwrap()), vec![]);
+    assert_eq!(unreachable(&[]), vec![]);
+  }
+
+  #[test]
+  fn test_loops() {
+    let program = parse_input(EXAMPLE).unwrap();
+    assert_eq!(loops(&program), vec![vec![1, 2, 3, 4, 6, 7]]);
+
+    // a self loop, a loop that can be left, and a straight line
+    let program = assemble("a: jmp a\nb: acc -1\njnz b\nout\nc: jmp c").unwrap();
+    assert_eq!(loops(&program), vec![vec![0], vec![1, 2], vec![4]]);
+    assert_eq!(
+      loops(&assemble("acc +1\njmp +1").unwrap()),
+      Vec::<Vec<usize>>::new()
+    );
+
+    // long enough to overflow the stack with recursion
+    let program: Vec<Instruction> = (0..100_000)
+      .map(|_| (Opcode::NoOp, 0))
+      .chain(Some((Opcode::Jump, -100_000)))
+      .collect();
+    assert_eq!(loops(&program)[0].len(), 100_001);
+  }
+
+  #[test]
+  fn test_analyze() {
+    let program = parse_input(EXAMPLE).unwrap();
+    let report = analyze(&program);
+    assert_eq!(
+      report,
+      Report {
+        length: 9,
+        result: Err(MachineError::InfiniteLoop { ip: 1 }),
+        accumulator: 5,
+        unreachable: vec![5, 8],
+        loops: vec![vec![1, 2, 3, 4, 6, 7]],
+        patches: Ok(vec![Repair {
+          index: 7,
+          patch: (Opcode::NoOp, -4),
+          accumulator: 8,
+        }]),
+      }
+    );
+    assert_eq!(
+      report.to_string(),
+      "Instructions: 9
+Unpatched: Infinite loop, instruction #1 would run twice (acc = 5)
+Unreachable: #5, #8
+Loops:
+  #1-#4, #6-#7
+Terminating patches:
+  #7 nop -4: acc = 8"
+    );
+
+    // a program that ends already can still have patches that end it
+    let report = analyze(&assemble("nop +2\nacc +2\nnop -2").unwrap());
+    assert_eq!(report.result, Ok(()));
+    assert_eq!(report.patches.as_ref().map(|x| x.len()), Ok(1));
+    assert_eq!(
+      report.to_string().lines().skip(1).collect::<Vec<&str>>(),
+      vec![
+        "Unpatched: ends with acc = 2",
+        "Unreachable: none",
+        "Loops: none",
+        "Terminating patches:",
+        "  #0 jmp +2: acc = 0"
+      ]
+    );
   }
 
   #[test]
   fn test_parse_input() {
-    let input = "nop +0
-acc +1
-jmp +4
-acc +3
-jmp -3
-acc -99
-acc +1
-jmp -4
-acc +6";
     assert_eq!(
-      parse_input(input),
-      [
+      parse_input(EXAMPLE),
+      Ok(vec![
         (Opcode::NoOp, 0),
         (Opcode::Accumulate, 1),
         (Opcode::Jump, 4),
@@ -198,7 +1440,15 @@ acc +6";
         (Opcode::Accumulate, 1),
         (Opcode::Jump, -4),
         (Opcode::Accumulate, 6),
-      ]
+      ])
+    );
+    assert_eq!(
+      parse_input("nop +0\nacc"),
+      Err(AocError::parse(2, 1, "Invalid instruction `acc`"))
+    );
+    assert_eq!(
+      parse_input("nop +0\nadd +1"),
+      Err(AocError::parse(2, 1, "Unrecognized opcode add"))
     );
   }
 }
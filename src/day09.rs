@@ -1,6 +1,8 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::{parse_lines, AocError};
+
 fn is_valid_number(value: u64, list: &[u64]) -> bool {
   for i in 0..list.len() {
     for j in i + 1..list.len() {
@@ -13,17 +15,22 @@ fn is_valid_number(value: u64, list: &[u64]) -> bool {
   false
 }
 
-fn find_first_invalid_number(list: &[u64], preamble_length: usize) -> Result<(u64, usize), &str> {
+fn find_first_invalid_number(
+  list: &[u64],
+  preamble_length: usize,
+) -> Result<(u64, usize), AocError> {
   for i in preamble_length..list.len() {
     if !is_valid_number(list[i], &list[i - preamble_length..i]) {
       return Ok((list[i], i));
     }
   }
 
-  Err("Could not find an invalid number")
+  Err(AocError::NoSolution(
+    "Could not find an invalid number".to_string(),
+  ))
 }
 
-fn find_summands_for_number(target: u64, list: &[u64]) -> Result<&[u64], &str> {
+fn find_summands_for_number(target: u64, list: &[u64]) -> Result<&[u64], AocError> {
   for i in 0..list.len() {
     for j in i + 1..list.len() {
       let sum: u64 = list[i..j].iter().sum();
@@ -33,25 +40,30 @@ fn find_summands_for_number(target: u64, list: &[u64]) -> Result<&[u64], &str> {
     }
   }
 
-  Err("Could not find a contiguous set of summands")
+  Err(AocError::NoSolution(
+    "Could not find a contiguous set of summands".to_string(),
+  ))
 }
 
 #[aoc_generator(day9)]
-pub fn parse_input(input: &str) -> Vec<u64> {
-  input.lines().map(|x| x.parse().unwrap()).collect()
+pub fn parse_input(input: &str) -> Result<Vec<u64>, AocError> {
+  parse_lines(input, |x| {
+    x.parse::<u64>()
+      .map_err(|_| format!("Invalid number `{}`", x))
+  })
 }
 
 #[aoc(day9, part1)]
-pub fn solve_part1(list: &[u64]) -> Result<u64, &str> {
+pub fn solve_part1(list: &[u64]) -> Result<u64, AocError> {
   const PREAMBLE: usize = 25;
-  Ok(find_first_invalid_number(&list, PREAMBLE)?.0)
+  Ok(find_first_invalid_number(list, PREAMBLE)?.0)
 }
 
 #[aoc(day9, part2)]
-pub fn solve_part2(list: &[u64]) -> Result<u64, &str> {
+pub fn solve_part2(list: &[u64]) -> Result<u64, AocError> {
   const PREAMBLE: usize = 25;
 
-  let (target, index) = find_first_invalid_number(&list, PREAMBLE)?;
+  let (target, index) = find_first_invalid_number(list, PREAMBLE)?;
   let summands = find_summands_for_number(target, &list[..index])?;
 
   Ok(summands.iter().max().unwrap() + summands.iter().min().unwrap())
@@ -93,6 +105,10 @@ mod tests {
   #[test]
   fn test_parse_input() {
     let input = "35\n20\n15\n25\n47\n40";
-    assert_eq!(parse_input(input), vec![35, 20, 15, 25, 47, 40]);
+    assert_eq!(parse_input(input), Ok(vec![35, 20, 15, 25, 47, 40]));
+    assert_eq!(
+      parse_input("35\n-20"),
+      Err(AocError::parse(2, 1, "Invalid number `-20`"))
+    );
   }
 }
@@ -1,41 +1,46 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::{parse_lines, AocError};
 use std::collections::HashMap;
 
 type Cache = HashMap<Vec<u64>, u64>;
 
-fn find_n_paths(list: &[u64], cache: &mut Cache) -> u64 {
+// returns `None` if the amount of paths does not fit in a u64
+fn find_n_paths(list: &[u64], cache: &mut Cache) -> Option<u64> {
   if list.len() <= 1 {
-    return list.len() as u64;
+    return Some(list.len() as u64);
   }
 
   if let Some(path_count) = cache.get(list) {
-    return *path_count;
+    return Some(*path_count);
   }
 
   let mut result: u64 = 0;
 
   for i in 1..list.len() {
     if list[i] - list[0] <= 3 {
-      result += find_n_paths(&list[i..], cache);
+      result = result.checked_add(find_n_paths(&list[i..], cache)?)?;
     }
   }
 
   cache.insert(list.to_vec(), result);
 
-  result
+  Some(result)
 }
 
 #[aoc_generator(day10)]
-pub fn parse_input(input: &str) -> Vec<u64> {
-  let mut list: Vec<u64> = input.lines().map(|x| x.parse().unwrap()).collect();
-  list.sort();
-  list
+pub fn parse_input(input: &str) -> Result<Vec<u64>, AocError> {
+  let mut list = parse_lines(input, |x| {
+    x.parse::<u64>()
+      .map_err(|_| format!("Invalid joltage `{}`", x))
+  })?;
+  list.sort_unstable();
+  Ok(list)
 }
 
 #[aoc(day10, part1)]
-pub fn solve_part1(adapters: &[u64]) -> Result<u64, &str> {
+pub fn solve_part1(adapters: &[u64]) -> Result<u64, AocError> {
   let mut delta_freqs: [u64; 3] = [0, 0, 1]; // [1 jolt, 2 jolts, 3 jolts] freq
   for i in 0..adapters.len() {
     let last_joltage = if i > 0 { adapters[i - 1] } else { 0 };
@@ -44,7 +49,8 @@ pub fn solve_part1(adapters: &[u64]) -> Result<u64, &str> {
     match delta {
       1..=3 => delta_freqs[(delta - 1) as usize] += 1,
       _ => {
-        return Err("Difference between adapters is too high");
+        let error_msg = format!("Invalid difference of {} jolts between adapters", delta);
+        return Err(AocError::InvalidInput(error_msg));
       }
     }
   }
@@ -53,40 +59,52 @@ pub fn solve_part1(adapters: &[u64]) -> Result<u64, &str> {
 }
 
 #[aoc(day10, part2)]
-pub fn solve_part2(adapters: &[u64]) -> Result<u64, &str> {
-  let full_list = [
-    vec![0],
-    adapters.to_vec(),
-    vec![adapters[adapters.len() - 1] + 3],
-  ]
-  .concat();
+pub fn solve_part2(adapters: &[u64]) -> Result<u64, AocError> {
+  let last = adapters
+    .last()
+    .ok_or_else(|| AocError::InvalidInput("There are no adapters".to_string()))?;
+  let device = last
+    .checked_add(3)
+    .ok_or_else(|| AocError::Overflow("Device joltage does not fit in 64 bits".to_string()))?;
+  let full_list = [vec![0], adapters.to_vec(), vec![device]].concat();
 
   let mut cache = Cache::new();
-  Ok(find_n_paths(&full_list, &mut cache))
+  find_n_paths(&full_list, &mut cache)
+    .ok_or_else(|| AocError::Overflow("Too many ways to arrange the adapters".to_string()))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn test_parse_input() {
+    assert_eq!(parse_input("16\n10\n15"), Ok(vec![10, 15, 16]));
+    assert_eq!(
+      parse_input("16\nx"),
+      Err(AocError::parse(2, 1, "Invalid joltage `x`"))
+    );
+  }
+
   #[test]
   fn test_solve_part1() {
     let mut input = [16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
     input.sort();
     assert_eq!(solve_part1(&input), Ok(7 * 5));
+    assert!(solve_part1(&[1, 5]).is_err());
   }
 
   #[test]
   fn test_find_n_paths() {
-    assert_eq!(find_n_paths(&vec![], &mut Cache::new()), 0);
-    assert_eq!(find_n_paths(&vec![0], &mut Cache::new()), 1);
-    assert_eq!(find_n_paths(&vec![0, 10], &mut Cache::new()), 0);
+    assert_eq!(find_n_paths(&vec![], &mut Cache::new()), Some(0));
+    assert_eq!(find_n_paths(&vec![0], &mut Cache::new()), Some(1));
+    assert_eq!(find_n_paths(&vec![0, 10], &mut Cache::new()), Some(0));
     assert_eq!(
       find_n_paths(&vec![0, 1, 3, 4, 5, 10, 11], &mut Cache::new()),
-      0
+      Some(0)
     );
-    assert_eq!(find_n_paths(&vec![0, 2, 3], &mut Cache::new()), 2);
-    assert_eq!(find_n_paths(&vec![0, 1, 2, 3], &mut Cache::new()), 4);
+    assert_eq!(find_n_paths(&vec![0, 2, 3], &mut Cache::new()), Some(2));
+    assert_eq!(find_n_paths(&vec![0, 1, 2, 3], &mut Cache::new()), Some(4));
   }
 
   #[test]
@@ -101,5 +119,12 @@ mod tests {
     ];
     input_alt.sort();
     assert_eq!(solve_part2(&input_alt), Ok(19208));
+    assert!(solve_part2(&[]).is_err());
+    assert_eq!(
+      solve_part2(&[1, u64::MAX]),
+      Err(AocError::Overflow(
+        "Device joltage does not fit in 64 bits".to_string()
+      ))
+    );
   }
 }
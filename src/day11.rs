@@ -1,4 +1,6 @@
 use aoc_runner_derive::aoc;
+
+use crate::error::AocError;
 use std::cmp;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -7,6 +9,36 @@ enum Cell {
   Floor,
 }
 
+// parses a map of one character per cell, checking that every row has the
+// same width; returns the cells row by row, and the width
+pub fn parse_cells<T, F>(map: &str, parse_cell: F) -> Result<(Vec<T>, usize), AocError>
+where
+  F: Fn(char) -> Option<T>,
+{
+  let mut width = 0;
+  let mut cells: Vec<T> = vec![];
+
+  for (y, line) in map.lines().enumerate() {
+    for (x, c) in line.chars().enumerate() {
+      let cell = parse_cell(c).ok_or_else(|| {
+        let error_msg = format!("Unexpected character `{}`", c);
+        AocError::parse(y + 1, x + 1, &error_msg)
+      })?;
+      cells.push(cell);
+    }
+
+    let line_width = line.chars().count();
+    if y == 0 {
+      width = line_width;
+    } else if line_width != width {
+      let error_msg = format!("Expected {} cells, found {}", width, line_width);
+      return Err(AocError::parse(y + 1, 1, &error_msg));
+    }
+  }
+
+  Ok((cells, width))
+}
+
 pub struct Grid {
   cells: Vec<Cell>,
   previous_cells: Vec<Cell>,
@@ -15,35 +47,22 @@ pub struct Grid {
 }
 
 impl Grid {
-  pub fn new(map: &str) -> Self {
-    let mut width = 0;
-    let cells: Vec<Cell> = map
-      .lines()
-      .map(|line| {
-        width = line.len();
-        line
-          .chars()
-          .map(|c| match c {
-            'L' => Cell::Seat(false),
-            '.' => Cell::Floor,
-            '#' => Cell::Seat(true),
-            _ => {
-              panic!("wrong char {:?}", c);
-            }
-          })
-          .collect::<Vec<Cell>>()
-      })
-      .collect::<Vec<Vec<Cell>>>()
-      .concat();
+  pub fn new(map: &str) -> Result<Self, AocError> {
+    let (cells, width) = parse_cells(map, |c| match c {
+      'L' => Some(Cell::Seat(false)),
+      '.' => Some(Cell::Floor),
+      '#' => Some(Cell::Seat(true)),
+      _ => None,
+    })?;
 
-    let height = if width > 0 { cells.len() / width } else { 0 };
+    let height = cells.len().checked_div(width).unwrap_or(0);
 
-    Grid {
-      cells: cells,
+    Ok(Grid {
+      cells,
       previous_cells: vec![],
-      width: width,
-      height: height,
-    }
+      width,
+      height,
+    })
   }
 
   fn get_cell_at(&self, x: i64, y: i64) -> Option<Cell> {
@@ -113,7 +132,7 @@ impl Grid {
           .filter(|x| *x)
           .collect();
 
-        if *cell == Cell::Seat(false) && occupied.len() == 0 {
+        if *cell == Cell::Seat(false) && occupied.is_empty() {
           Cell::Seat(true)
         } else if *cell == Cell::Seat(true) && occupied.len() >= tolerance {
           Cell::Seat(false)
@@ -126,19 +145,19 @@ impl Grid {
 }
 
 #[aoc(day11, part1)]
-pub fn solve_part1(input: &str) -> u64 {
-  let mut grid = Grid::new(input);
+pub fn solve_part1(input: &str) -> Result<u64, AocError> {
+  let mut grid = Grid::new(input)?;
 
   while !grid.is_stable() {
     grid.step(1, 4);
   }
 
-  grid.get_occupied_amount() as u64
+  Ok(grid.get_occupied_amount() as u64)
 }
 
 #[aoc(day11, part2)]
-pub fn solve_part2(input: &str) -> u64 {
-  let mut grid = Grid::new(input);
+pub fn solve_part2(input: &str) -> Result<u64, AocError> {
+  let mut grid = Grid::new(input)?;
 
   let distance = cmp::max(grid.width, grid.height);
 
@@ -146,7 +165,7 @@ pub fn solve_part2(input: &str) -> u64 {
     grid.step(distance, 5);
   }
 
-  grid.get_occupied_amount() as u64
+  Ok(grid.get_occupied_amount() as u64)
 }
 
 #[cfg(test)]
@@ -156,7 +175,7 @@ mod tests {
   #[test]
   fn test_grid_constructor() {
     let input = ".L.\n#L.\n...\nL#.";
-    let grid = Grid::new(input);
+    let grid = Grid::new(input).unwrap();
 
     assert_eq!(grid.width, 3);
     assert_eq!(grid.height, 4);
@@ -179,10 +198,22 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_grid_constructor_errors() {
+    assert_eq!(
+      Grid::new(".L.\n#X.").err(),
+      Some(AocError::parse(2, 2, "Unexpected character `X`"))
+    );
+    assert_eq!(
+      Grid::new(".L.\n#L").err(),
+      Some(AocError::parse(2, 1, "Expected 3 cells, found 2"))
+    );
+  }
+
   #[test]
   fn test_get_cell_at() {
     let input = "L.#";
-    let grid = Grid::new(input);
+    let grid = Grid::new(input).unwrap();
 
     assert_eq!(grid.get_cell_at(-1, 0), None);
     assert_eq!(grid.get_cell_at(4, 0), None);
@@ -209,7 +240,7 @@ L.LLLLL.LL
     .chars()
     .filter(|&c| c != ' ')
     .collect();
-    assert_eq!(solve_part1(&input), 37);
+    assert_eq!(solve_part1(&input), Ok(37));
   }
 
   #[test]
@@ -228,6 +259,6 @@ L.LLLLL.LL
     .chars()
     .filter(|&c| c != ' ')
     .collect();
-    assert_eq!(solve_part2(&input), 26);
+    assert_eq!(solve_part2(&input), Ok(26));
   }
 }
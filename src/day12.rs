@@ -1,6 +1,7 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::AocError;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -12,7 +13,7 @@ pub enum Dir {
 }
 
 impl Dir {
-  fn to_delta(&self) -> (i64, i64) {
+  fn to_delta(self) -> (i64, i64) {
     match self {
       Self::North => (0, -1),
       Self::South => (0, 1),
@@ -22,6 +23,17 @@ impl Dir {
   }
 }
 
+// `position + delta * times`, unless that doesn't fit in 64 bits
+fn offset(position: (i64, i64), delta: (i64, i64), times: i64) -> Result<(i64, i64), AocError> {
+  let axis = |p: i64, d: i64| d.checked_mul(times).and_then(|x| p.checked_add(x));
+  match (axis(position.0, delta.0), axis(position.1, delta.1)) {
+    (Some(x), Some(y)) => Ok((x, y)),
+    _ => Err(AocError::Overflow(
+      "Position does not fit in 64 bits".to_string(),
+    )),
+  }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Action {
   Move(Dir, i64),
@@ -33,13 +45,20 @@ impl FromStr for Action {
   type Err = String;
 
   fn from_str(text: &str) -> Result<Self, Self::Err> {
-    let action_code = text
-      .chars()
+    let mut chars = text.chars();
+    let action_code = chars
       .next()
-      .expect(format!("can't parse action code from `{}`", text).as_str());
-    let value: i64 = text[1..]
+      .ok_or(format!("Can't parse action code from `{}`", text))?;
+    let value: i64 = chars
+      .as_str()
       .parse()
-      .expect(format!("can't parse action value from `{}`", text).as_str());
+      .map_err(|_| format!("Can't parse action value from `{}`", text))?;
+
+    // we only allow +-90 angle increments
+    if (action_code == 'R' || action_code == 'L') && value % 90 != 0 {
+      return Err(format!("Invalid rotation angle in `{}`", text));
+    }
+
     match action_code {
       'F' => Ok(Action::Advance(value)),
       'R' => Ok(Action::Rotate(value)),
@@ -69,15 +88,14 @@ impl Ship {
     }
   }
 
-  fn move_to(&mut self, direction: Dir, distance: i64) {
-    let delta = direction.to_delta();
-    self.position.0 += distance * delta.0;
-    self.position.1 += distance * delta.1;
+  fn move_to(&mut self, direction: Dir, distance: i64) -> Result<(), AocError> {
+    self.position = offset(self.position, direction.to_delta(), distance)?;
+    Ok(())
   }
 
   fn rotate(&mut self, angle: i64) {
     const DIRS: [Dir; 4] = [Dir::East, Dir::South, Dir::West, Dir::North];
-    let steps = angle / 90; // we only allow +-90 angle increments
+    let steps = angle / 90;
 
     let current = DIRS.iter().position(|&x| x == self.facing).unwrap() as i64;
     let index = (steps + current).rem_euclid(4);
@@ -85,26 +103,37 @@ impl Ship {
     self.facing = DIRS[index as usize];
   }
 
-  fn move_to_waypoint(&mut self, times: i64) {
-    self.position.0 += self.waypoint.unwrap().position.0 * times;
-    self.position.1 += self.waypoint.unwrap().position.1 * times;
+  fn move_to_waypoint(&mut self, waypoint: Waypoint, times: i64) -> Result<(), AocError> {
+    self.position = offset(self.position, waypoint.position, times)?;
+    Ok(())
   }
 
-  pub fn exec(&mut self, action: Action) {
+  pub fn exec(&mut self, action: Action) -> Result<(), AocError> {
     if let Some(mut waypoint) = self.waypoint {
       match action {
-        Action::Move(dir, value) => waypoint.move_to(dir, value),
-        Action::Rotate(angle) => waypoint.rotate(angle),
-        Action::Advance(value) => self.move_to_waypoint(value),
+        Action::Move(dir, value) => waypoint.move_to(dir, value)?,
+        Action::Rotate(angle) => waypoint.rotate(angle)?,
+        Action::Advance(value) => self.move_to_waypoint(waypoint, value)?,
       }
       self.waypoint = Some(waypoint);
     } else {
       match action {
-        Action::Advance(value) => self.move_to(self.facing, value),
-        Action::Move(dir, value) => self.move_to(dir, value),
+        Action::Advance(value) => self.move_to(self.facing, value)?,
+        Action::Move(dir, value) => self.move_to(dir, value)?,
         Action::Rotate(angle) => self.rotate(angle),
       };
     }
+
+    Ok(())
+  }
+
+  // manhattan distance from the starting point
+  fn distance(&self) -> Result<i64, AocError> {
+    let (x, y) = self.position;
+    x.checked_abs()
+      .zip(y.checked_abs())
+      .and_then(|(x, y)| x.checked_add(y))
+      .ok_or_else(|| AocError::Overflow("Distance does not fit in 64 bits".to_string()))
   }
 }
 
@@ -114,53 +143,68 @@ struct Waypoint {
 }
 
 impl Waypoint {
-  pub fn move_to(&mut self, direction: Dir, distance: i64) {
-    let delta = direction.to_delta();
-    self.position.0 += distance * delta.0;
-    self.position.1 += distance * delta.1;
+  pub fn move_to(&mut self, direction: Dir, distance: i64) -> Result<(), AocError> {
+    self.position = offset(self.position, direction.to_delta(), distance)?;
+    Ok(())
   }
 
-  pub fn rotate(&mut self, angle: i64) {
+  pub fn rotate(&mut self, angle: i64) -> Result<(), AocError> {
     let angle = angle.rem_euclid(360); // clamp the angle to 360º
+    let (x, y) = self.position;
+    let overflow = || AocError::Overflow("Waypoint does not fit in 64 bits".to_string());
+    let negate = |value: i64| value.checked_neg().ok_or_else(overflow);
 
     match angle {
-      90 | -270 => self.position = (-self.position.1, self.position.0), // (-y, x)
-      -90 | 270 => self.position = (self.position.1, -self.position.0), // (y, -x)
-      180 | -180 => self.position = (-self.position.0, -self.position.1), // (-x, -y)
+      0 => {}
+      90 | -270 => self.position = (negate(y)?, x), // (-y, x)
+      -90 | 270 => self.position = (y, negate(x)?), // (y, -x)
+      180 | -180 => self.position = (negate(x)?, negate(y)?), // (-x, -y)
       _ => unreachable!(),
     };
+
+    Ok(())
   }
 }
 
 #[aoc_generator(day12)]
-pub fn parse_input(input: &str) -> Vec<Action> {
-  input
-    .split_whitespace()
-    .map(|x| Action::from_str(x).unwrap())
-    .collect()
+pub fn parse_input(input: &str) -> Result<Vec<Action>, AocError> {
+  let mut actions = vec![];
+
+  for (y, line) in input.lines().enumerate() {
+    let mut column = 1;
+    for token in line.split(' ') {
+      if !token.is_empty() {
+        let action = Action::from_str(token).map_err(|err| AocError::parse(y + 1, column, &err))?;
+        actions.push(action);
+      }
+      column += token.chars().count() + 1;
+    }
+  }
+
+  Ok(actions)
 }
 
 #[aoc(day12, part1)]
-pub fn solve_part1(actions: &[Action]) -> i64 {
+pub fn solve_part1(actions: &[Action]) -> Result<i64, AocError> {
   let mut ship = Ship::new();
 
-  for action in actions.into_iter() {
-    ship.exec(*action);
+  for action in actions.iter() {
+    ship.exec(*action)?;
   }
 
-  ship.position.0.abs() + ship.position.1.abs()
+  ship.distance()
 }
 
 #[aoc(day12, part2)]
-pub fn solve_part2(actions: &[Action]) -> i64 {
+pub fn solve_part2(actions: &[Action]) -> Result<i64, AocError> {
   let mut ship = Ship::new();
   ship.waypoint = Some(Waypoint { position: (10, -1) });
 
-  for action in actions.into_iter() {
-    ship.exec(*action);
+  for action in actions.iter() {
+    ship.exec(*action)?;
   }
 
-  ship.position.0.abs() + ship.position.1.abs()
+  ship.distance()
 }
 
 #[cfg(test)]
@@ -177,18 +221,21 @@ mod tests {
     assert_eq!(Action::from_str("E2"), Ok(Action::Move(Dir::East, 2)));
     assert_eq!(Action::from_str("W2"), Ok(Action::Move(Dir::West, 2)));
     assert!(Action::from_str("?10").is_err());
+    assert!(Action::from_str("F").is_err());
+    assert!(Action::from_str("").is_err());
+    assert!(Action::from_str("R45").is_err());
   }
 
   #[test]
   fn test_exec_rotate() {
     let mut ship = Ship::new();
     ship.facing = Dir::East;
-    ship.exec(Action::Rotate(-90));
+    ship.exec(Action::Rotate(-90)).unwrap();
     assert_eq!(ship.facing, Dir::North);
 
     let mut ship = Ship::new();
     ship.facing = Dir::North;
-    ship.exec(Action::Rotate(180));
+    ship.exec(Action::Rotate(180)).unwrap();
     assert_eq!(ship.facing, Dir::South);
   }
 
@@ -197,13 +244,13 @@ mod tests {
     let mut ship = Ship::new();
     ship.position = (0, 0);
 
-    ship.exec(Action::Move(Dir::North, 2));
+    ship.exec(Action::Move(Dir::North, 2)).unwrap();
     assert_eq!(ship.position, (0, -2));
-    ship.exec(Action::Move(Dir::East, 4));
+    ship.exec(Action::Move(Dir::East, 4)).unwrap();
     assert_eq!(ship.position, (4, -2));
-    ship.exec(Action::Move(Dir::West, 5));
+    ship.exec(Action::Move(Dir::West, 5)).unwrap();
     assert_eq!(ship.position, (-1, -2));
-    ship.exec(Action::Move(Dir::South, 5));
+    ship.exec(Action::Move(Dir::South, 5)).unwrap();
     assert_eq!(ship.position, (-1, 3));
   }
 
@@ -212,7 +259,7 @@ mod tests {
     let mut ship = Ship::new();
     ship.facing = Dir::North;
 
-    ship.exec(Action::Advance(10));
+    ship.exec(Action::Advance(10)).unwrap();
     assert_eq!(ship.position, (0, -10));
   }
 
@@ -221,30 +268,36 @@ mod tests {
     let input = "F10 N3 F7 R90 F11";
     assert_eq!(
       parse_input(input),
-      vec![
+      Ok(vec![
         Action::Advance(10),
         Action::Move(Dir::North, 3),
         Action::Advance(7),
         Action::Rotate(90),
         Action::Advance(11),
-      ]
+      ])
+    );
+    assert_eq!(
+      parse_input("F10\nN3 Fx"),
+      Err(AocError::parse(2, 4, "Can't parse action value from `Fx`"))
     );
   }
 
   #[test]
   fn test_rotate_waypoint() {
     let mut waypoint = Waypoint { position: (1, -2) };
-    waypoint.rotate(90);
+    waypoint.rotate(90).unwrap();
     assert_eq!(waypoint.position, (2, 1));
-    waypoint.rotate(-90);
+    waypoint.rotate(-90).unwrap();
     assert_eq!(waypoint.position, (1, -2));
-    waypoint.rotate(180);
+    waypoint.rotate(180).unwrap();
     assert_eq!(waypoint.position, (-1, 2));
-    waypoint.rotate(-180);
+    waypoint.rotate(-180).unwrap();
     assert_eq!(waypoint.position, (1, -2));
-    waypoint.rotate(270);
+    waypoint.rotate(270).unwrap();
     assert_eq!(waypoint.position, (-2, -1));
-    waypoint.rotate(-270);
+    waypoint.rotate(-270).unwrap();
+    assert_eq!(waypoint.position, (1, -2));
+    waypoint.rotate(360).unwrap();
     assert_eq!(waypoint.position, (1, -2));
   }
 
@@ -257,7 +310,7 @@ mod tests {
       Action::Rotate(90),
       Action::Advance(11),
     ];
-    assert_eq!(solve_part1(&input), 25);
+    assert_eq!(solve_part1(&input), Ok(25));
   }
 
   #[test]
@@ -269,6 +322,33 @@ mod tests {
       Action::Rotate(90),
       Action::Advance(11),
     ];
-    assert_eq!(solve_part2(&input), 286);
+    assert_eq!(solve_part2(&input), Ok(286));
+  }
+
+  #[test]
+  fn test_overflow() {
+    let overflow = |what: &str| AocError::Overflow(format!("{} does not fit in 64 bits", what));
+    let far = Action::Advance(i64::MAX);
+    assert_eq!(solve_part1(&[far, far]), Err(overflow("Position")));
+    assert_eq!(solve_part2(&[far]), Err(overflow("Position")));
+    assert_eq!(
+      solve_part1(&[
+        Action::Move(Dir::West, i64::MAX),
+        Action::Move(Dir::North, 1)
+      ]),
+      Err(overflow("Distance"))
+    );
+    assert_eq!(
+      solve_part2(&[
+        Action::Move(Dir::North, i64::MAX),
+        Action::Move(Dir::North, 1)
+      ]),
+      Err(overflow("Position"))
+    );
+
+    let mut waypoint = Waypoint {
+      position: (i64::MIN, 0),
+    };
+    assert_eq!(waypoint.rotate(180), Err(overflow("Waypoint")));
   }
 }
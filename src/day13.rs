@@ -1,53 +1,79 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::AocError;
+
+fn parse_bus(text: &str) -> Option<Option<u64>> {
+  match text {
+    "x" => Some(None),
+    _ => match text.parse() {
+      Ok(0) | Err(_) => None,
+      Ok(bus) => Some(Some(bus)),
+    },
+  }
+}
+
 #[aoc_generator(day13)]
-pub fn parse_input(input: &str) -> (u64, Vec<Option<u64>>) {
+pub fn parse_input(input: &str) -> Result<(u64, Vec<Option<u64>>), AocError> {
   let mut input_iter = input.lines();
 
-  let departure = input_iter.next().unwrap().parse().unwrap();
-  let buses: Vec<Option<u64>> = input_iter
+  let raw_departure = input_iter
     .next()
-    .unwrap()
-    .split(',')
-    .map(|x| x.parse().ok())
-    .collect();
+    .ok_or_else(|| AocError::parse(1, 1, "Missing departure time"))?;
+  let departure = raw_departure.parse().map_err(|_| {
+    let error_msg = format!("Invalid departure time `{}`", raw_departure);
+    AocError::parse(1, 1, &error_msg)
+  })?;
+
+  let raw_buses = input_iter
+    .next()
+    .ok_or_else(|| AocError::parse(2, 1, "Missing bus list"))?;
+  let mut buses: Vec<Option<u64>> = vec![];
+  let mut column = 1;
+  for raw_bus in raw_buses.split(',') {
+    let bus = parse_bus(raw_bus).ok_or_else(|| {
+      let error_msg = format!("Invalid bus ID `{}`", raw_bus);
+      AocError::parse(2, column, &error_msg)
+    })?;
+    buses.push(bus);
+    column += raw_bus.chars().count() + 1;
+  }
 
-  (departure, buses)
+  Ok((departure, buses))
 }
 
 #[aoc(day13, part1)]
-pub fn solve_part1(input: &(u64, Vec<Option<u64>>)) -> u64 {
+pub fn solve_part1(input: &(u64, Vec<Option<u64>>)) -> Result<u64, AocError> {
   let departure = input.0;
-  let buses: Vec<u64> = input
-    .1
-    .iter()
-    .filter(|x| x.is_some())
-    .map(|x| x.unwrap())
-    .collect();
+  let buses: Vec<u64> = input.1.iter().flatten().copied().collect();
 
-  let earliest_departures = buses.iter().map(|&bus| {
-    let timestamp = (departure as f64 / bus as f64).ceil() as u64 * bus;
-    (bus, timestamp)
-  });
+  let earliest_departures = buses
+    .iter()
+    .map(|&bus| {
+      let trips = departure.div_ceil(bus);
+      checked_mul(trips, bus).map(|timestamp| (bus, timestamp))
+    })
+    .collect::<Result<Vec<(u64, u64)>, AocError>>()?;
 
   let (bus, timestamp) = earliest_departures
+    .into_iter()
     .min_by(|a, b| a.1.cmp(&b.1))
-    .expect("No bus was found");
+    .ok_or_else(|| AocError::InvalidInput("No bus was found".to_string()))?;
   let waiting_time = timestamp - departure;
 
-  bus * waiting_time
+  checked_mul(bus, waiting_time)
 }
 
-fn find_common_timestamp_in_list(buses: Vec<(u64, u64)>) -> Result<u64, String> {
-  if buses.len() == 0 {
-    return Err("Empty bus list".to_string());
+fn find_common_timestamp_in_list(buses: Vec<(u64, u64)>) -> Result<u64, AocError> {
+  if buses.is_empty() {
+    return Err(AocError::InvalidInput("Empty bus list".to_string()));
   }
   if buses.len() == 1 {
     return Ok(buses[0].1);
   }
 
-  let mut t = 0;
+  let overflow = || AocError::Overflow("Timestamp does not fit in 64 bits".to_string());
+  let mut t: u64 = 0;
   let mut coeff = 1;
   for i in 1..buses.len() {
     let (curr_gap, curr_bus_id) = buses[i];
@@ -56,21 +82,35 @@ fn find_common_timestamp_in_list(buses: Vec<(u64, u64)>) -> Result<u64, String>
     // NOTE: this should be minimum common multiple of every ID up to this point,
     // but since all the numbers in the input are primes, just a plain
     // multiplication works
-    coeff = coeff * prev_bus_id;
-
-    loop {
-      if (t + curr_gap) % curr_bus_id == 0 {
+    coeff = checked_mul(coeff, prev_bus_id)?;
+
+    // the remainder of `t` cycles in at most `curr_bus_id` steps, so if no
+    // step matched the IDs weren't primes after all
+    let mut found = false;
+    for _ in 0..curr_bus_id {
+      if t.checked_add(curr_gap).ok_or_else(overflow)? % curr_bus_id == 0 {
+        found = true;
         break;
       }
-      t += coeff;
+      t = t.checked_add(coeff).ok_or_else(overflow)?;
+    }
+
+    if !found {
+      let error_msg = format!("Bus {} can never depart at its offset", curr_bus_id);
+      return Err(AocError::NoSolution(error_msg));
     }
   }
 
   Ok(t)
 }
 
+fn checked_mul(a: u64, b: u64) -> Result<u64, AocError> {
+  a.checked_mul(b)
+    .ok_or_else(|| AocError::Overflow(format!("{} * {} does not fit in 64 bits", a, b)))
+}
+
 #[aoc(day13, part2)]
-pub fn solve_part2(input: &(u64, Vec<Option<u64>>)) -> Result<u64, String> {
+pub fn solve_part2(input: &(u64, Vec<Option<u64>>)) -> Result<u64, AocError> {
   let buses = &input.1;
 
   find_common_timestamp_in_list(
@@ -93,7 +133,7 @@ mod tests {
 
     assert_eq!(
       parse_input(input),
-      (
+      Ok((
         939,
         vec![
           Some(7),
@@ -105,8 +145,15 @@ mod tests {
           Some(31),
           Some(19)
         ]
-      )
+      ))
     );
+    assert_eq!(
+      parse_input("939\n7,13,y,x"),
+      Err(AocError::parse(2, 6, "Invalid bus ID `y`"))
+    );
+    assert!(parse_input("939\n7,0").is_err());
+    assert!(parse_input("939").is_err());
+    assert!(parse_input("abc\n7").is_err());
   }
 
   #[test]
@@ -123,7 +170,17 @@ mod tests {
       Some(19),
     ];
 
-    assert_eq!(solve_part1(&(departure, buses)), 295);
+    assert_eq!(solve_part1(&(departure, buses)), Ok(295));
+    assert!(solve_part1(&(departure, vec![None])).is_err());
+    assert_eq!(
+      solve_part1(&(1, vec![Some(1 << 40)])),
+      Err(AocError::Overflow(format!(
+        "{} * {} does not fit in 64 bits",
+        1u64 << 40,
+        (1u64 << 40) - 1
+      )))
+    );
+    assert!(solve_part1(&(u64::MAX, vec![Some(2)])).is_err());
   }
 
   #[test]
@@ -148,5 +205,17 @@ mod tests {
 
     let buses = vec![Some(1789), Some(37), Some(47), Some(1889)];
     assert_eq!(solve_part2(&(0, buses)), Ok(1202161486));
+
+    let buses = vec![Some(4), Some(6)];
+    assert!(solve_part2(&(0, buses)).is_err());
+
+    // the timestamp reaches u64::MAX before bus 5 fits its offset
+    let buses = vec![Some(u64::MAX / 3), Some(5)];
+    assert_eq!(
+      solve_part2(&(0, buses)),
+      Err(AocError::Overflow(
+        "Timestamp does not fit in 64 bits".to_string()
+      ))
+    );
   }
 }
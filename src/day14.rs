@@ -1,6 +1,7 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::error::{parse_lines, AocError};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
@@ -10,7 +11,7 @@ use std::str::FromStr;
 pub struct Mask {
   or: u64,
   and: u64,
-  raw: String,
+  floating: u64,
 }
 
 impl Mask {
@@ -18,59 +19,16 @@ impl Mask {
     (self.or | x) & self.and
   }
 
-  pub fn apply_v2(&self, x: u64) -> Vec<u64> {
-    let value = format!("{:036b}", x);
-    let floating_mask: String = value
-      .chars()
-      .enumerate()
-      .map(|(i, value_char)| match self.raw.chars().nth(i).unwrap() {
-        'x' => 'x',
-        '0' => value_char,
-        '1' => '1',
-        _ => unreachable!(),
-      })
-      .collect();
-    self.get_values_for_floating(&floating_mask)
-  }
-
-  fn get_values_for_floating(&self, floating_mask: &str) -> Vec<u64> {
-    let mut partials: Vec<String> = vec![];
-
-    for char in floating_mask.chars() {
-      match char {
-        'x' => {
-          if partials.len() == 0 {
-            partials.push("0".to_string());
-            partials.push("1".to_string());
-          } else {
-            let mut zero_partials = partials.clone();
-            let mut one_partials = partials.clone();
-            for partial in zero_partials.iter_mut() {
-              partial.push_str("0");
-            }
-            for partial in one_partials.iter_mut() {
-              partial.push_str("1");
-            }
-
-            partials = [zero_partials, one_partials].concat();
-          }
-        }
-        _ => {
-          if partials.len() == 0 {
-            partials.push(char.to_string());
-          } else {
-            for partial in partials.iter_mut() {
-              partial.push_str(&char.to_string())
-            }
-          }
-        }
-      }
-    }
+  // every address written by version 2, in the same order as the puzzle
+  // expands them: the highest floating bit is the one that changes first
+  pub fn apply_v2(&self, x: u64) -> impl Iterator<Item = u64> {
+    let base = (x | self.or) & !self.floating;
+    Subsets::new(self.floating.reverse_bits()).map(move |bits| base | bits.reverse_bits())
+  }
 
-    partials
-      .iter()
-      .map(|x| u64::from_str_radix(x, 2).unwrap())
-      .collect()
+  // every address written by version 2, without expanding them
+  pub fn address_pattern(&self, address: u64) -> AddressPattern {
+    AddressPattern::new(address | self.or, self.floating)
   }
 }
 
@@ -79,23 +37,178 @@ impl Default for Mask {
     Mask {
       and: 0xffff_ffff_ffff_ffff,
       or: 0x0000_0000_0000_0000,
-      raw: format!("{:036b}", 0),
+      floating: 0x0000_0000_0000_0000,
     }
   }
 }
 
 impl FromStr for Mask {
-  type Err = std::num::ParseIntError;
+  type Err = String;
 
   fn from_str(text: &str) -> Result<Self, Self::Err> {
-    let and_mask = text.to_lowercase().replace('x', "1");
-    let or_mask = text.to_lowercase().replace('x', "0");
+    // masks apply to 36-bit values
+    if text.is_empty() || text.len() > 36 {
+      return Err(format!("Invalid mask `{}`", text));
+    }
+
+    let mut mask = Mask {
+      or: 0,
+      and: 0,
+      floating: 0,
+    };
+    for c in text.chars() {
+      let (or, and, floating) = match c {
+        '0' => (0, 0, 0),
+        '1' => (1, 1, 0),
+        'x' | 'X' => (0, 1, 1),
+        _ => return Err(format!("Invalid mask `{}`", text)),
+      };
+      mask.or = mask.or << 1 | or;
+      mask.and = mask.and << 1 | and;
+      mask.floating = mask.floating << 1 | floating;
+    }
+
+    Ok(mask)
+  }
+}
+
+// every subset of the bits of a mask, in increasing order, starting with the
+// empty one
+pub struct Subsets {
+  mask: u64,
+  next: Option<u64>,
+}
+
+impl Subsets {
+  pub fn new(mask: u64) -> Self {
+    Subsets {
+      mask,
+      next: Some(0),
+    }
+  }
+}
+
+impl Iterator for Subsets {
+  type Item = u64;
+
+  fn next(&mut self) -> Option<u64> {
+    let subset = self.next?;
+    // borrows propagate through the bits outside of the mask, which the
+    // final `&` clears
+    let following = subset.wrapping_sub(self.mask) & self.mask;
+    self.next = if following == 0 {
+      None
+    } else {
+      Some(following)
+    };
+
+    Some(subset)
+  }
+}
+
+// a set of addresses: the bits in `floating` take every possible value, while
+// the rest are the ones in `fixed`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AddressPattern {
+  fixed: u64,
+  floating: u64,
+}
+
+impl AddressPattern {
+  pub fn new(fixed: u64, floating: u64) -> Self {
+    AddressPattern {
+      fixed: fixed & !floating,
+      floating,
+    }
+  }
+
+  // how many addresses match the pattern
+  pub fn size(&self) -> u128 {
+    1 << self.floating.count_ones()
+  }
+
+  pub fn contains(&self, address: u64) -> bool {
+    (address ^ self.fixed) & !self.floating == 0
+  }
+
+  // patterns share addresses unless a bit is fixed to different values
+  pub fn intersects(&self, other: &Self) -> bool {
+    (self.fixed ^ other.fixed) & !self.floating & !other.floating == 0
+  }
+
+  pub fn intersection(&self, other: &Self) -> Option<Self> {
+    if !self.intersects(other) {
+      return None;
+    }
+    Some(AddressPattern::new(
+      self.fixed | other.fixed,
+      self.floating & other.floating,
+    ))
+  }
+
+  // the addresses of this pattern that aren't in `other`, as disjoint
+  // patterns; each bit that floats here but is fixed in `other` splits off
+  // the addresses where that bit differs from `other`
+  pub fn subtract(&self, other: &Self) -> Vec<Self> {
+    if !self.intersects(other) {
+      return vec![*self];
+    }
+
+    let mut pieces = vec![];
+    let mut rest = *self;
+    let mut bits = self.floating & !other.floating;
+    while bits != 0 {
+      let bit = bits & bits.wrapping_neg();
+      bits &= !bit;
+      rest.floating &= !bit;
+      pieces.push(AddressPattern::new(
+        (rest.fixed & !bit) | (!other.fixed & bit),
+        rest.floating,
+      ));
+      rest.fixed = (rest.fixed & !bit) | (other.fixed & bit);
+    }
 
-    Ok(Self {
-      and: u64::from_str_radix(&and_mask, 2)?,
-      or: u64::from_str_radix(&or_mask, 2)?,
-      raw: format!("{:0>36}", text.to_lowercase()),
-    })
+    pieces
+  }
+}
+
+// memory written with version 2, kept as disjoint address patterns so the
+// floating addresses are never expanded
+#[derive(Debug, Default)]
+pub struct FloatingMemory {
+  regions: Vec<(AddressPattern, u64)>,
+}
+
+impl FloatingMemory {
+  pub fn write(&mut self, pattern: AddressPattern, value: u64) {
+    let mut regions = Vec::with_capacity(self.regions.len() + 1);
+    for (region, region_value) in self.regions.drain(..) {
+      if region.intersects(&pattern) {
+        let pieces = region.subtract(&pattern);
+        regions.extend(pieces.into_iter().map(|x| (x, region_value)));
+      } else {
+        regions.push((region, region_value));
+      }
+    }
+    regions.push((pattern, value));
+
+    self.regions = regions;
+  }
+
+  pub fn read(&self, address: u64) -> Option<u64> {
+    self
+      .regions
+      .iter()
+      .find(|(region, _)| region.contains(address))
+      .map(|(_, value)| *value)
+  }
+
+  pub fn sum(&self) -> u128 {
+    self
+      .regions
+      .iter()
+      .map(|(region, value)| region.size() * *value as u128)
+      .sum()
   }
 }
 
@@ -119,12 +232,8 @@ impl FromStr for Instruction {
 
     if RE_SETMASK.is_match(text) {
       let captured = RE_SETMASK.captures(text).unwrap();
-      let mask = captured
-        .name("mask")
-        .unwrap()
-        .as_str()
-        .parse::<Mask>()
-        .or(Err(error_msg.to_string()))?;
+      let mask = captured.name("mask").unwrap().as_str();
+      let mask = mask.parse::<Mask>().or(Err(error_msg.to_string()))?;
       Ok(Self::SetMask(mask))
     } else if RE_MEM.is_match(text) {
       let captured = RE_MEM.captures(text).unwrap();
@@ -153,15 +262,25 @@ pub enum Version {
   V2,
 }
 
+// a machine runs a whole program with a single version, each of them writing
+// to its own memory
 pub struct Machine {
   memory: HashMap<u64, u64>,
+  floating_memory: FloatingMemory,
   mask: Mask,
 }
 
+impl Default for Machine {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Machine {
   pub fn new() -> Self {
     Machine {
       memory: HashMap::new(),
+      floating_memory: Default::default(),
       mask: Default::default(),
     }
   }
@@ -176,9 +295,8 @@ impl Machine {
           self.memory.insert(*address, self.mask.apply_v1(*value));
         }
         Version::V2 => {
-          for addr in self.mask.apply_v2(*address).iter() {
-            self.memory.insert(*addr, *value);
-          }
+          let pattern = self.mask.address_pattern(*address);
+          self.floating_memory.write(pattern, *value);
         }
       },
     }
@@ -188,38 +306,54 @@ impl Machine {
     // reset internal state
     self.mask = Default::default();
     self.memory = HashMap::new();
+    self.floating_memory = Default::default();
 
     // exec all the instructions
-    for instruction in program.into_iter() {
-      self.exec(&instruction, version);
+    for instruction in program.iter() {
+      self.exec(instruction, version);
     }
   }
+
+  pub fn read(&self, address: u64) -> Option<u64> {
+    match self.memory.get(&address) {
+      Some(value) => Some(*value),
+      None => self.floating_memory.read(address),
+    }
+  }
+
+  pub fn sum(&self) -> u128 {
+    let sum: u128 = self.memory.values().map(|&x| x as u128).sum();
+    sum + self.floating_memory.sum()
+  }
 }
 
 #[aoc_generator(day14)]
-pub fn parse_input(input: &str) -> Result<Vec<Instruction>, String> {
-  let instructions: Vec<Instruction> = input
-    .lines()
-    .map(|x| x.parse::<Instruction>().unwrap())
-    .collect();
-
-  Ok(instructions)
+pub fn parse_input(input: &str) -> Result<Vec<Instruction>, AocError> {
+  parse_lines(input, str::parse::<Instruction>)
 }
 
-#[aoc(day14, part1)]
-pub fn solve_part1(program: &[Instruction]) -> u64 {
+fn run_program(program: &[Instruction], version: Version) -> Result<u64, AocError> {
   let mut machine = Machine::new();
-  machine.run(program, Version::V1);
+  machine.run(program, version);
+
+  let sum = machine.sum();
+  if sum > u64::MAX as u128 {
+    return Err(AocError::Overflow(format!(
+      "Memory sum {} doesn't fit in 64 bits",
+      sum
+    )));
+  }
+  Ok(sum as u64)
+}
 
-  machine.memory.values().sum()
+#[aoc(day14, part1)]
+pub fn solve_part1(program: &[Instruction]) -> Result<u64, AocError> {
+  run_program(program, Version::V1)
 }
 
 #[aoc(day14, part2)]
-pub fn solve_part2(program: &[Instruction]) -> u64 {
-  let mut machine = Machine::new();
-  machine.run(program, Version::V2);
-
-  machine.memory.values().sum()
+pub fn solve_part2(program: &[Instruction]) -> Result<u64, AocError> {
+  run_program(program, Version::V2)
 }
 
 #[cfg(test)]
@@ -233,11 +367,13 @@ mod tests {
       Ok(Mask {
         or: 0b0100,
         and: 0b1101,
-        raw: "00000000000000000000000000000000x10x".to_string()
+        floating: 0b1001
       })
     );
 
     assert!("x1200x".parse::<Mask>().is_err());
+    assert!("".parse::<Mask>().is_err());
+    assert!("X".repeat(37).parse::<Mask>().is_err());
   }
 
   #[test]
@@ -250,10 +386,26 @@ mod tests {
     assert_eq!(mask.apply_v1(11), 11);
   }
 
+  #[test]
+  fn test_subsets() {
+    assert_eq!(
+      Subsets::new(0b1010).collect::<Vec<u64>>(),
+      vec![0b0000, 0b0010, 0b1000, 0b1010]
+    );
+    assert_eq!(Subsets::new(0).collect::<Vec<u64>>(), vec![0]);
+    assert_eq!(Subsets::new(u64::MAX).nth(5), Some(5));
+  }
+
   #[test]
   fn test_mask_apply_v2() {
     let mask: Mask = "00X1001X".parse().unwrap();
-    assert_eq!(mask.apply_v2(42), vec![26, 58, 27, 59]);
+    assert_eq!(
+      mask.apply_v2(42).collect::<Vec<u64>>(),
+      vec![26, 58, 27, 59]
+    );
+
+    let mask: Mask = "X".repeat(36).parse().unwrap();
+    assert_eq!(mask.apply_v2(0).nth(1), Some(1 << 35));
   }
 
   #[test]
@@ -295,10 +447,11 @@ mod tests {
     let mut machine: Machine = Machine::new();
     machine.mask = "00X1001X".parse().unwrap();
     machine.exec(&Instruction::Mem(42, 11), Version::V2);
-    assert_eq!(machine.memory.get(&26), Some(&11));
-    assert_eq!(machine.memory.get(&27), Some(&11));
-    assert_eq!(machine.memory.get(&58), Some(&11));
-    assert_eq!(machine.memory.get(&59), Some(&11));
+    assert_eq!(machine.read(26), Some(11));
+    assert_eq!(machine.read(27), Some(11));
+    assert_eq!(machine.read(58), Some(11));
+    assert_eq!(machine.read(59), Some(11));
+    assert_eq!(machine.read(42), None);
   }
 
   #[test]
@@ -328,6 +481,15 @@ mem[8] = 0";
         Instruction::Mem(8, 0),
       ])
     );
+    assert_eq!(
+      parse_input("mem[8] = 11\nmem[x] = 1"),
+      Err(AocError::parse(
+        2,
+        1,
+        "Cannot parse instruction: mem[x] = 1"
+      ))
+    );
+    assert!(parse_input(&format!("mask = {}", "X".repeat(37))).is_err());
   }
 
   #[test]
@@ -339,7 +501,7 @@ mem[8] = 0";
       Instruction::Mem(8, 0),
     ];
 
-    assert_eq!(solve_part1(&program), 165);
+    assert_eq!(solve_part1(&program), Ok(165));
   }
 
   #[test]
@@ -350,6 +512,98 @@ mem[8] = 0";
       Instruction::SetMask("00000000000000000000000000000000X0XX".parse().unwrap()),
       Instruction::Mem(26, 1),
     ];
-    assert_eq!(solve_part2(&program), 208);
+    assert_eq!(solve_part2(&program), Ok(208));
+
+    // the example of part 1 has 34 floating bits
+    let program = vec![
+      Instruction::SetMask("XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X".parse().unwrap()),
+      Instruction::Mem(8, 11),
+      Instruction::Mem(7, 101),
+      Instruction::Mem(8, 0),
+    ];
+    assert_eq!(solve_part2(&program), Ok(101 << 34));
+  }
+
+  #[test]
+  fn test_address_pattern() {
+    let mask: Mask = "00X1001X".parse().unwrap();
+    let pattern = mask.address_pattern(42);
+    assert_eq!(pattern, AddressPattern::new(0b0001_1010, 0b0010_0001));
+    assert_eq!(pattern.size(), 4);
+    for address in 0..64 {
+      assert_eq!(
+        pattern.contains(address),
+        [26, 27, 58, 59].contains(&address)
+      );
+    }
+
+    let other = AddressPattern::new(0b0011_0000, 0b0000_1111);
+    assert!(pattern.intersects(&other));
+    assert_eq!(
+      pattern.intersection(&other),
+      Some(AddressPattern::new(0b0011_1010, 0b0000_0001))
+    );
+    assert_eq!(pattern.intersection(&AddressPattern::new(0, 0)), None);
+  }
+
+  #[test]
+  fn test_address_pattern_subtract() {
+    let pattern = AddressPattern::new(0, 0b111);
+    let other = AddressPattern::new(0b010, 0b001);
+    let pieces = pattern.subtract(&other);
+
+    let mut addresses: Vec<u64> = (0..8)
+      .filter(|&x| pieces.iter().any(|piece| piece.contains(x)))
+      .collect();
+    addresses.sort();
+    assert_eq!(addresses, vec![0, 1, 4, 5, 6, 7]);
+    assert_eq!(pieces.iter().map(|x| x.size()).sum::<u128>(), 6);
+
+    assert_eq!(pattern.subtract(&pattern), vec![]);
+    assert_eq!(other.subtract(&AddressPattern::new(0b100, 0)), vec![other]);
+  }
+
+  #[test]
+  fn test_floating_memory_matches_expansion() {
+    // pseudo-random 8-bit masks and addresses, from a linear congruential
+    // generator
+    let mut seed: u64 = 2020;
+    let mut random = |n: u64| {
+      seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+      (seed >> 33) % n
+    };
+
+    let mut memory = FloatingMemory::default();
+    let mut expanded: HashMap<u64, u64> = HashMap::new();
+    for _ in 0..50 {
+      let mask: String = (0..8)
+        .map(|_| ['0', '1', 'X'][random(3) as usize])
+        .collect();
+      let mask: Mask = mask.parse().unwrap();
+      let (address, value) = (random(256), random(1000));
+
+      memory.write(mask.address_pattern(address), value);
+      for x in mask.apply_v2(address) {
+        expanded.insert(x, value);
+      }
+    }
+
+    for address in 0..256 {
+      assert_eq!(memory.read(address), expanded.get(&address).cloned());
+    }
+    assert_eq!(memory.sum(), expanded.values().map(|&x| x as u128).sum());
+  }
+
+  #[test]
+  fn test_floating_memory() {
+    let mut memory = FloatingMemory::default();
+    memory.write(AddressPattern::new(0, (1 << 36) - 1), 1);
+    memory.write(AddressPattern::new(0b101, 0), 5);
+    memory.write(AddressPattern::new(0b100, 0b11), 2);
+
+    assert_eq!(memory.read(0), Some(1));
+    assert_eq!(memory.read(0b101), Some(2));
+    assert_eq!(memory.read(1 << 36), None);
+    assert_eq!(memory.sum(), (1 << 36) - 4 + 4 * 2);
   }
 }
@@ -1,88 +1,260 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
-use std::collections::HashMap;
+use crate::error::AocError;
+use std::collections::{BTreeMap, HashSet};
 
-pub struct Game {
-  turn: u64,
-  memory: HashMap<u64, (u64, u64)>, // (latest_turn, age)
-  starters: Vec<u64>,
-  last_number: u64,
+// a memory rule decides the number spoken after each turn; it's told about
+// every number spoken, starters included, and keeps whatever it needs of them
+pub trait Rule {
+  // `number` has just been spoken in `turn` (1-based); returns the next number
+  fn next(&mut self, number: u32, turn: u32) -> u32;
+
+  // hint that the numbers about to be spoken are lower than `size`
+  fn reserve(&mut self, _size: usize) {}
+}
+
+// grows a per-number memory so `index` fits, at least doubling its size
+fn grow<T: Clone + Default>(memory: &mut Vec<T>, index: usize) {
+  if index >= memory.len() {
+    memory.resize((index + 1).max(memory.len() * 2), T::default());
+  }
+}
+
+// the puzzle rule: how many turns apart the number is from the previous time
+// it was spoken, or 0 if it's new
+#[derive(Debug, Default)]
+pub struct VanEck {
+  // turn in which each number was last spoken, only meaningful for the
+  // numbers flagged in `seen`; that bitset is small enough to stay in cache,
+  // so new numbers don't need a lookup in the much bigger `memory`
+  memory: Vec<u32>,
+  seen: Vec<u64>,
+}
+
+impl VanEck {
+  #[cold]
+  fn next_growing(&mut self, number: u32, turn: u32) -> u32 {
+    self.reserve(number as usize + 1);
+    self.next(number, turn)
+  }
+}
+
+impl Rule for VanEck {
+  fn next(&mut self, number: u32, turn: u32) -> u32 {
+    let index = number as usize;
+    let (word, bit) = (index / 64, 1 << (index % 64));
+    let latest_turn = match self.memory.get_mut(index) {
+      Some(latest_turn) => latest_turn,
+      None => return self.next_growing(number, turn),
+    };
+
+    let age = if self.seen[word] & bit == 0 {
+      self.seen[word] |= bit;
+      0
+    } else {
+      turn - *latest_turn
+    };
+    *latest_turn = turn;
+
+    age
+  }
+
+  fn reserve(&mut self, size: usize) {
+    if self.memory.len() < size {
+      grow(&mut self.memory, size - 1);
+      self.seen.resize(self.memory.len() / 64 + 1, 0);
+    }
+  }
+}
+
+// how many times the number had been spoken before, or 0 if it's new
+#[derive(Debug, Default)]
+pub struct TimesSpoken {
+  counts: Vec<u32>,
+}
+
+impl Rule for TimesSpoken {
+  fn next(&mut self, number: u32, _turn: u32) -> u32 {
+    grow(&mut self.counts, number as usize);
+    let count = &mut self.counts[number as usize];
+    *count += 1;
+    *count - 1
+  }
+
+  fn reserve(&mut self, size: usize) {
+    grow(&mut self.counts, size.saturating_sub(1));
+  }
+}
+
+// how many turns apart the number is from the time before the previous one it
+// was spoken, or 0 if it had been spoken less than twice
+#[derive(Debug, Default)]
+pub struct SecondToLast {
+  // the last two turns in which each number was spoken, 0 meaning never
+  memory: Vec<(u32, u32)>,
+}
+
+impl Rule for SecondToLast {
+  fn next(&mut self, number: u32, turn: u32) -> u32 {
+    grow(&mut self.memory, number as usize);
+    let (last, second_to_last) = self.memory[number as usize];
+    self.memory[number as usize] = (turn, last);
+    match second_to_last {
+      0 => 0,
+      x => turn - x,
+    }
+  }
+
+  fn reserve(&mut self, size: usize) {
+    grow(&mut self.memory, size.saturating_sub(1));
+  }
+}
+
+pub struct Game<R = VanEck> {
+  turn: u32,
+  starters: Vec<u32>,
+  rule: R,
+  last_number: u32,
+  next_number: u32,
+}
+
+impl Game<VanEck> {
+  pub fn new(starters: &[u32]) -> Self {
+    Game::with_rule(starters, VanEck::default())
+  }
 }
 
-impl Game {
-  pub fn new(starters: &[u64]) -> Self {
-    Game {
+impl<R: Rule> Game<R> {
+  pub fn with_rule(starters: &[u32], rule: R) -> Self {
+    let mut game = Game {
       turn: 0,
-      memory: HashMap::new(),
       starters: starters.to_vec(),
+      rule,
       last_number: 0,
-    }
+      next_number: 0,
+    };
+    let size = starters.iter().max().map_or(0, |&x| x as usize + 1);
+    game.rule.reserve(size);
+    game
   }
 
-  fn record_turn(&mut self, number: u64) {
-    let mut age = 0;
+  fn step(&mut self) -> u32 {
+    let number = match self.starters.get(self.turn as usize) {
+      Some(&starter) => starter,
+      None => self.next_number,
+    };
 
-    if let Some((latest_turn, _)) = self.memory.get(&number) {
-      age = self.turn - latest_turn;
+    self.turn += 1;
+    self.last_number = number;
+    self.next_number = self.rule.next(number, self.turn);
+    number
+  }
+
+  // the number spoken in turn `n` (1-based) of the game, or none if that turn
+  // has already been played
+  pub fn nth_spoken(&mut self, n: u32) -> Option<u64> {
+    if n == 0 || n < self.turn {
+      return None;
     }
 
-    self.memory.insert(number, (self.turn, age));
-  }
+    // with the puzzle rule, numbers are always lower than the turn count
+    self.rule.reserve(n as usize);
+    while self.turn < n && (self.turn as usize) < self.starters.len() {
+      self.step();
+    }
+    // same as `step` once the starters are spoken
+    let rule = &mut self.rule;
+    let (mut last_number, mut next_number) = (self.last_number, self.next_number);
+    for turn in self.turn + 1..=n {
+      last_number = next_number;
+      next_number = rule.next(last_number, turn);
+    }
+    self.turn = n;
+    self.last_number = last_number;
+    self.next_number = next_number;
 
-  fn get_turns_apart_for(&self, number: u64) -> u64 {
-    let (_, age) = self.memory.get(&number).unwrap();
-    *age
+    Some(self.last_number as u64)
   }
 }
 
-impl Iterator for Game {
+impl<R: Rule> Iterator for Game<R> {
   type Item = u64;
 
   fn next(&mut self) -> Option<u64> {
-    let mut number = 0;
-
-    // first turns: starters are spoken
-    if (self.turn as usize) < self.starters.len() {
-      number = self.starters[self.turn as usize];
-    }
-    // following turns: number exists
-    else if self.memory.contains_key(&self.last_number) {
-      number = self.get_turns_apart_for(self.last_number);
+    if self.turn == u32::MAX {
+      return None;
     }
-    // following turns: number doesn't exist -> speak 0
 
-    self.record_turn(number);
-    self.turn += 1;
-    self.last_number = number;
+    Some(self.step() as u64)
+  }
+}
+
+// the first number spoken for a second time in a sequence, with the turn
+// (1-based) in which that happened
+pub fn first_repeat<I: IntoIterator<Item = u64>>(sequence: I) -> Option<(usize, u64)> {
+  let mut spoken = HashSet::new();
+  sequence
+    .into_iter()
+    .enumerate()
+    .find(|(_, number)| !spoken.insert(*number))
+    .map(|(i, number)| (i + 1, number))
+}
 
-    Some(number)
+// how many times each number appears in a sequence; pass `game.take(n)` to
+// get the histogram of the first `n` turns
+pub fn histogram<I: IntoIterator<Item = u64>>(sequence: I) -> BTreeMap<u64, usize> {
+  let mut histogram = BTreeMap::new();
+  for number in sequence {
+    *histogram.entry(number).or_insert(0) += 1;
   }
+
+  histogram
+}
+
+// the largest number in a sequence, with the first turn (1-based) in which it
+// was spoken
+pub fn largest<I: IntoIterator<Item = u64>>(sequence: I) -> Option<(usize, u64)> {
+  sequence
+    .into_iter()
+    .enumerate()
+    .fold(None, |largest, (i, number)| match largest {
+      Some((_, max)) if max >= number => largest,
+      _ => Some((i + 1, number)),
+    })
 }
 
-fn run_game(starters: &[u64], turns: u64) -> Result<u64, String> {
-  let mut game = Game::new(starters);
-  game
-    .nth((turns - 1) as usize)
-    .ok_or(format!("Game ended before turn #{}", turns))
+fn run_game(starters: &[u32], turns: u32) -> Result<u64, AocError> {
+  let error_msg = format!("Game ended before turn #{}", turns);
+  Game::new(starters)
+    .nth_spoken(turns)
+    .ok_or(AocError::NoSolution(error_msg))
 }
 
 #[aoc_generator(day15)]
-fn parse_input(input: &str) -> Result<Vec<u64>, std::num::ParseIntError> {
-  input
-    .split(',')
-    .map(|x| x.parse::<u64>())
-    .collect::<Result<Vec<u64>, std::num::ParseIntError>>()
+pub fn parse_input(input: &str) -> Result<Vec<u32>, AocError> {
+  let mut starters = vec![];
+  let mut column = 1;
+  for x in input.trim_end().split(',') {
+    let starter = x.parse::<u32>().map_err(|_| {
+      let error_msg = format!("Invalid starting number `{}`", x);
+      AocError::parse(1, column, &error_msg)
+    })?;
+    starters.push(starter);
+    column += x.chars().count() + 1;
+  }
+
+  Ok(starters)
 }
 
 #[aoc(day15, part1)]
-fn solve_part1(input: &[u64]) -> Result<u64, String> {
+pub fn solve_part1(input: &[u32]) -> Result<u64, AocError> {
   run_game(input, 2020)
 }
 
 #[aoc(day15, part2)]
-fn solve_part2(input: &[u64]) -> Result<u64, String> {
-  run_game(input, 30000000)
+pub fn solve_part2(input: &[u32]) -> Result<u64, AocError> {
+  run_game(input, 30_000_000)
 }
 
 #[cfg(test)]
@@ -110,9 +282,85 @@ mod tests {
     assert_eq!(game.next(), Some(0));
   }
 
+  #[test]
+  fn test_game_repeated_starters() {
+    let game = Game::new(&[1, 1, 2]);
+    assert_eq!(game.take(6).collect::<Vec<u64>>(), vec![1, 1, 2, 0, 0, 1]);
+  }
+
+  #[test]
+  fn test_nth_spoken() {
+    let expected: Vec<u64> = Game::new(&[0, 3, 6]).take(2020).collect();
+    for n in [1, 3, 4, 10, 2020].iter() {
+      assert_eq!(
+        Game::new(&[0, 3, 6]).nth_spoken(*n),
+        Some(expected[*n as usize - 1])
+      );
+    }
+
+    let mut game = Game::new(&[0, 3, 6]);
+    assert_eq!(game.nth_spoken(0), None);
+    assert_eq!(game.nth_spoken(10), Some(0));
+    assert_eq!(game.nth_spoken(10), Some(0));
+    assert_eq!(game.nth_spoken(9), None);
+    assert_eq!(game.next(), Some(2));
+  }
+
+  #[test]
+  fn test_times_spoken_rule() {
+    let game = Game::with_rule(&[0, 3, 6], TimesSpoken::default());
+    assert_eq!(
+      game.take(11).collect::<Vec<u64>>(),
+      vec![0, 3, 6, 0, 1, 0, 2, 0, 3, 1, 1]
+    );
+  }
+
+  #[test]
+  fn test_second_to_last_rule() {
+    let mut game = Game::with_rule(&[0, 3, 6], SecondToLast::default());
+    assert_eq!(
+      game.by_ref().take(10).collect::<Vec<u64>>(),
+      vec![0, 3, 6, 0, 0, 4, 0, 3, 0, 4]
+    );
+    assert_eq!(game.nth_spoken(10), Some(4));
+  }
+
+  #[test]
+  fn test_first_repeat() {
+    assert_eq!(first_repeat(Game::new(&[0, 3, 6]).take(10)), Some((4, 0)));
+    assert_eq!(first_repeat(Game::new(&[0, 3, 6]).take(3)), None);
+  }
+
+  #[test]
+  fn test_histogram() {
+    let expected: BTreeMap<u64, usize> = [(0, 4), (1, 1), (3, 3), (4, 1), (6, 1)]
+      .iter()
+      .cloned()
+      .collect();
+    assert_eq!(histogram(Game::new(&[0, 3, 6]).take(10)), expected);
+  }
+
+  #[test]
+  fn test_largest() {
+    assert_eq!(largest(Game::new(&[0, 3, 6]).take(10)), Some((3, 6)));
+    assert_eq!(largest(vec![2, 5, 1, 5]), Some((2, 5)));
+    assert_eq!(largest(vec![]), None);
+  }
+
   #[test]
   fn test_parse_input() {
     assert_eq!(parse_input("0,3,6"), Ok(vec![0, 3, 6]));
+    assert_eq!(parse_input("0,3,6\n"), Ok(vec![0, 3, 6]));
+    assert_eq!(
+      parse_input("0,3,x"),
+      Err(AocError::parse(1, 5, "Invalid starting number `x`"))
+    );
+  }
+
+  #[test]
+  fn test_run_game() {
+    assert_eq!(run_game(&[0, 3, 6], 4), Ok(0));
+    assert!(run_game(&[0, 3, 6], 0).is_err());
   }
 
   #[test]
@@ -123,4 +371,10 @@ mod tests {
     assert_eq!(solve_part1(&vec![1, 2, 3]), Ok(27));
     assert_eq!(solve_part1(&vec![3, 1, 2]), Ok(1836));
   }
+
+  #[test]
+  fn test_solve_part2() {
+    assert_eq!(solve_part2(&[0, 3, 6]), Ok(175594));
+    assert_eq!(solve_part2(&[3, 1, 2]), Ok(362));
+  }
 }
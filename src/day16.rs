@@ -1,6 +1,9 @@
 use aoc_runner_derive::aoc;
 use aoc_runner_derive::aoc_generator;
 
+use crate::assignment::{resolve_unique, AssignmentError};
+use crate::error::{blocks, AocError};
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
@@ -17,7 +20,7 @@ impl FromStr for RangeValidator {
       .map(|x| x.parse::<u64>().map_err(|_| error_msg.clone()))
       .collect::<Result<Vec<u64>, String>>()?;
 
-    let min = values.get(0).ok_or(error_msg.clone())?;
+    let min = values.first().ok_or(error_msg.clone())?;
     let max = values.get(1).ok_or(error_msg.clone())?;
 
     Ok(Self(*min..=*max))
@@ -43,17 +46,13 @@ impl FromStr for NumFieldValidator {
       .split(" or ")
       .map(|x| x.parse::<RangeValidator>())
       .collect::<Result<Vec<RangeValidator>, String>>()?;
-    Ok(Self { ranges: ranges })
+    Ok(Self { ranges })
   }
 }
 
 impl NumFieldValidator {
   fn is_valid(&self, x: u64) -> bool {
-    self
-      .ranges
-      .iter()
-      .map(|range| range.is_valid(x))
-      .any(|res| !!res)
+    self.ranges.iter().any(|range| range.is_valid(x))
   }
 }
 
@@ -78,7 +77,7 @@ impl FromStr for Field {
 
     Ok(Self {
       name: name.to_string(),
-      validator: validator,
+      validator,
     })
   }
 }
@@ -88,6 +87,50 @@ pub struct TicketRules {
   fields: Vec<Field>,
 }
 
+impl TicketRules {
+  // returns the name of the field that goes in each column of the tickets
+  pub fn resolve_columns(&self, tickets: &[Ticket]) -> Result<Vec<&str>, AocError> {
+    let n_columns = self.fields.len();
+    if tickets
+      .iter()
+      .any(|ticket| ticket.fields.len() != n_columns)
+    {
+      let error_msg = format!("Tickets must have exactly {} fields", n_columns);
+      return Err(AocError::InvalidInput(error_msg));
+    }
+
+    // for each column, the fields whose validator accepts every value in it
+    let candidates: BTreeMap<usize, BTreeSet<usize>> = (0..n_columns)
+      .map(|column| {
+        let fields = (0..n_columns)
+          .filter(|&i| {
+            tickets
+              .iter()
+              .all(|ticket| self.fields[i].validator.is_valid(ticket.fields[column]))
+          })
+          .collect();
+        (column, fields)
+      })
+      .collect();
+
+    let resolved = resolve_unique(&candidates).map_err(|err| {
+      AocError::NoSolution(match err {
+        AssignmentError::Ambiguous(column) => format!("Ambiguous field for column #{}", column),
+        AssignmentError::Impossible(column) => {
+          format!("No field can be assigned to column #{}", column)
+        }
+      })
+    })?;
+
+    Ok(
+      resolved
+        .values()
+        .map(|&i| self.fields[i].name.as_str())
+        .collect(),
+    )
+  }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Ticket {
   fields: Vec<u64>,
@@ -101,7 +144,7 @@ impl FromStr for Ticket {
       .split(',')
       .map(|x| x.parse::<u64>())
       .collect::<Result<Vec<u64>, Self::Err>>()?;
-    Ok(Ticket { fields: fields })
+    Ok(Ticket { fields })
   }
 }
 
@@ -117,47 +160,62 @@ impl Ticket {
           .map(|field| &field.validator)
           .any(|validator| validator.is_valid(**x))
       })
-      .map(|x| *x)
+      .copied()
       .collect()
   }
+
+  fn is_valid(&self, rules: &TicketRules) -> bool {
+    self.get_invalid_fields(rules).is_empty()
+  }
+}
+
+fn parse_ticket(line: usize, text: &str) -> Result<Ticket, AocError> {
+  text
+    .parse::<Ticket>()
+    .map_err(|_| AocError::parse(line, 1, &format!("Invalid ticket `{}`", text)))
 }
 
 #[aoc_generator(day16)]
-pub fn parse_input(input: &str) -> Result<(TicketRules, Vec<Ticket>), String> {
-  let mut sections = input.split("\n\n");
+pub fn parse_input(input: &str) -> Result<(TicketRules, Vec<Ticket>), AocError> {
+  let mut sections = blocks(input);
 
   // 1st section -> validation rules
-  let raw_ruleset = sections.next().ok_or("No ruleset present".to_string())?;
+  let (first_line, raw_ruleset) = sections
+    .next()
+    .ok_or_else(|| AocError::InvalidInput("No ruleset present".to_string()))?;
   let fields: Vec<Field> = raw_ruleset
     .lines()
-    .map(|raw_rule| raw_rule.parse::<Field>())
-    .collect::<Result<Vec<Field>, String>>()?;
+    .enumerate()
+    .map(|(i, raw_rule)| {
+      raw_rule
+        .parse::<Field>()
+        .map_err(|err| AocError::parse(first_line + i, 1, &err))
+    })
+    .collect::<Result<Vec<Field>, AocError>>()?;
 
-  let ticket_error_msg = "No ticket present";
+  let ticket_error = || AocError::InvalidInput("No ticket present".to_string());
 
-  let tickets: Vec<Ticket> = vec![
-    // 2nd section -> your ticket
-    vec![sections
-      .next()
-      .ok_or(ticket_error_msg.to_string())?
-      .lines()
-      .skip(1) // NOTE: skip first line (it's a label)
-      .next()
-      .ok_or(ticket_error_msg.to_string())?
-      .parse::<Ticket>()
-      .or(Err("Invalid ticket".to_string()))?],
-    sections
-      .next()
-      .ok_or(ticket_error_msg.to_string())?
-      .lines()
-      .skip(1) // NOTE: skip first line (it's a label)
-      .map(|x| x.parse::<Ticket>())
-      .collect::<Result<Vec<Ticket>, _>>()
-      .or(Err("Invalid ticket".to_string()))?,
-  ]
-  .concat();
-
-  Ok((TicketRules { fields: fields }, tickets))
+  // 2nd section -> your ticket
+  let (first_line, raw_your_ticket) = sections.next().ok_or_else(ticket_error)?;
+  let your_ticket = raw_your_ticket
+    .lines()
+    .nth(1) // NOTE: skip first line (it's a label)
+    .ok_or_else(ticket_error)?;
+
+  // 3rd section -> nearby tickets
+  let (nearby_first_line, raw_nearby_tickets) = sections.next().ok_or_else(ticket_error)?;
+
+  let tickets: Vec<Ticket> = std::iter::once(parse_ticket(first_line + 1, your_ticket))
+    .chain(
+      raw_nearby_tickets
+        .lines()
+        .enumerate()
+        .skip(1) // NOTE: skip first line (it's a label)
+        .map(|(i, x)| parse_ticket(nearby_first_line + i, x)),
+    )
+    .collect::<Result<Vec<Ticket>, AocError>>()?;
+
+  Ok((TicketRules { fields }, tickets))
 }
 
 #[aoc(day16, part1)]
@@ -171,6 +229,30 @@ pub fn solve_part1(input: &(TicketRules, Vec<Ticket>)) -> u64 {
     .sum()
 }
 
+#[aoc(day16, part2)]
+pub fn solve_part2(input: &(TicketRules, Vec<Ticket>)) -> Result<u64, AocError> {
+  let (ruleset, tickets) = input;
+  let your_ticket = tickets
+    .first()
+    .ok_or_else(|| AocError::InvalidInput("No ticket present".to_string()))?;
+
+  let valid_tickets: Vec<Ticket> = tickets
+    .iter()
+    .filter(|ticket| ticket.is_valid(ruleset))
+    .cloned()
+    .collect();
+  let columns = ruleset.resolve_columns(&valid_tickets)?;
+
+  Ok(
+    columns
+      .iter()
+      .zip(your_ticket.fields.iter())
+      .filter(|(name, _)| name.starts_with("departure"))
+      .map(|(_, value)| value)
+      .product(),
+  )
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -231,6 +313,16 @@ nearby tickets:
 55,2,20
 38,6,12
 ";
+    assert_eq!(
+      parse_input(&input.replace("40,4,50", "40,x,50")).err(),
+      Some(AocError::parse(10, 1, "Invalid ticket `40,x,50`"))
+    );
+    assert_eq!(
+      parse_input(&input.replace("row: 6-11", "row 6-11")).err(),
+      Some(AocError::parse(2, 1, "Error parsing `row 6-11 or 33-44`"))
+    );
+    assert!(parse_input("class: 1-3 or 5-7").is_err());
+
     let (ruleset, tickets) = parse_input(input).unwrap();
     assert_eq!(
       ruleset,
@@ -326,4 +418,100 @@ nearby tickets:
 
     assert_eq!(solve_part1(&input), 71);
   }
+
+  #[test]
+  fn test_ticket_is_valid() {
+    let (ruleset, _) = parse_input(
+      "class: 1-3 or 5-7
+
+your ticket:
+7
+
+nearby tickets:
+4",
+    )
+    .unwrap();
+    assert!(Ticket { fields: vec![7] }.is_valid(&ruleset));
+    assert!(!Ticket { fields: vec![4] }.is_valid(&ruleset));
+  }
+
+  #[test]
+  fn test_resolve_columns() {
+    let input = "class: 0-1 or 4-19
+row: 0-5 or 8-19
+seat: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9
+";
+    let (ruleset, tickets) = parse_input(input).unwrap();
+    assert_eq!(
+      ruleset.resolve_columns(&tickets),
+      Ok(vec!["row", "class", "seat"])
+    );
+  }
+
+  #[test]
+  fn test_resolve_columns_errors() {
+    let input = "class: 0-1 or 4-19
+row: 0-5 or 8-19
+
+your ticket:
+1,1
+
+nearby tickets:
+1,1
+";
+    let (ruleset, tickets) = parse_input(input).unwrap();
+    assert_eq!(
+      ruleset.resolve_columns(&tickets),
+      Err(AocError::NoSolution(
+        "Ambiguous field for column #0".to_string()
+      ))
+    );
+
+    let input = "class: 0-1 or 4-19
+row: 0-5 or 8-19
+
+your ticket:
+7,6
+
+nearby tickets:
+7,6
+";
+    let (ruleset, tickets) = parse_input(input).unwrap();
+    assert_eq!(
+      ruleset.resolve_columns(&tickets),
+      Err(AocError::NoSolution(
+        "No field can be assigned to column #1".to_string()
+      ))
+    );
+
+    assert!(ruleset
+      .resolve_columns(&[Ticket { fields: vec![1] }])
+      .is_err());
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    let input = "departure location: 0-1 or 4-19
+row: 0-5 or 8-19
+departure station: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9
+40,4,50
+";
+    assert_eq!(solve_part2(&parse_input(input).unwrap()), Ok(12 * 13));
+  }
 }
@@ -0,0 +1,183 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::day11;
+use crate::error::AocError;
+use std::collections::{HashMap, HashSet};
+
+pub type Coords<const N: usize> = [i64; N];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PocketDimension<const N: usize> {
+  active: HashSet<Coords<N>>,
+}
+
+impl<const N: usize> PocketDimension<N> {
+  // the initial 2D slice is placed where all the other coordinates are 0, so
+  // there have to be at least two of them
+  pub fn new(slice: &[(i64, i64)]) -> Result<Self, AocError> {
+    if N < 2 {
+      let error_msg = format!("Can't place a 2D slice in {} dimensions", N);
+      return Err(AocError::InvalidInput(error_msg));
+    }
+
+    let active = slice
+      .iter()
+      .map(|&(x, y)| {
+        let mut coords = [0; N];
+        coords[0] = x;
+        coords[1] = y;
+        coords
+      })
+      .collect();
+
+    Ok(PocketDimension { active })
+  }
+
+  fn neighbor_deltas() -> Vec<Coords<N>> {
+    // every combination of -1, 0, 1 in each axis, except for (0, 0, …)
+    (0..3_usize.pow(N as u32))
+      .map(|mut combination| {
+        let mut delta = [0; N];
+        for axis in delta.iter_mut() {
+          *axis = (combination % 3) as i64 - 1;
+          combination /= 3;
+        }
+        delta
+      })
+      .filter(|delta| delta.iter().any(|&x| x != 0))
+      .collect()
+  }
+
+  pub fn is_active(&self, coords: &Coords<N>) -> bool {
+    self.active.contains(coords)
+  }
+
+  pub fn get_active_amount(&self) -> usize {
+    self.active.len()
+  }
+
+  pub fn step(&mut self) {
+    let deltas = Self::neighbor_deltas();
+    let mut active_neighbors: HashMap<Coords<N>, usize> = HashMap::new();
+
+    for cube in self.active.iter() {
+      for delta in deltas.iter() {
+        let mut neighbor = *cube;
+        for (axis, d) in neighbor.iter_mut().zip(delta.iter()) {
+          *axis += d;
+        }
+        *active_neighbors.entry(neighbor).or_insert(0) += 1;
+      }
+    }
+
+    self.active = active_neighbors
+      .into_iter()
+      .filter(|(coords, count)| *count == 3 || (*count == 2 && self.active.contains(coords)))
+      .map(|(coords, _)| coords)
+      .collect();
+  }
+}
+
+fn run_cycles<const N: usize>(slice: &[(i64, i64)], cycles: usize) -> Result<usize, AocError> {
+  let mut dimension = PocketDimension::<N>::new(slice)?;
+
+  for _ in 0..cycles {
+    dimension.step();
+  }
+
+  Ok(dimension.get_active_amount())
+}
+
+#[aoc_generator(day17)]
+pub fn parse_input(input: &str) -> Result<Vec<(i64, i64)>, AocError> {
+  let (cubes, width) = day11::parse_cells(input, |c| match c {
+    '#' => Some(true),
+    '.' => Some(false),
+    _ => None,
+  })?;
+
+  Ok(
+    cubes
+      .iter()
+      .enumerate()
+      .filter(|(_, &is_active)| is_active)
+      .map(|(i, _)| ((i % width) as i64, (i / width) as i64))
+      .collect(),
+  )
+}
+
+#[aoc(day17, part1)]
+pub fn solve_part1(slice: &[(i64, i64)]) -> Result<usize, AocError> {
+  run_cycles::<3>(slice, 6)
+}
+
+#[aoc(day17, part2)]
+pub fn solve_part2(slice: &[(i64, i64)]) -> Result<usize, AocError> {
+  run_cycles::<4>(slice, 6)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_input() {
+    assert_eq!(
+      parse_input(".#.\n..#\n###"),
+      Ok(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+    );
+    assert_eq!(
+      parse_input(".#.\n.L#"),
+      Err(AocError::parse(2, 2, "Unexpected character `L`"))
+    );
+    assert_eq!(
+      parse_input(".#.\n.#"),
+      Err(AocError::parse(2, 1, "Expected 3 cells, found 2"))
+    );
+  }
+
+  #[test]
+  fn test_neighbor_deltas() {
+    assert_eq!(PocketDimension::<2>::neighbor_deltas().len(), 8);
+    assert_eq!(PocketDimension::<3>::neighbor_deltas().len(), 26);
+    assert_eq!(PocketDimension::<4>::neighbor_deltas().len(), 80);
+  }
+
+  #[test]
+  fn test_step() {
+    let mut dimension =
+      PocketDimension::<3>::new(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]).unwrap();
+    dimension.step();
+
+    assert_eq!(dimension.get_active_amount(), 11);
+    assert!(dimension.is_active(&[0, 1, -1]));
+    assert!(dimension.is_active(&[1, 2, 0]));
+    assert!(!dimension.is_active(&[1, 0, 0]));
+  }
+
+  #[test]
+  fn test_new() {
+    assert!(PocketDimension::<2>::new(&[(1, 0)])
+      .unwrap()
+      .is_active(&[1, 0]));
+    assert_eq!(
+      PocketDimension::<1>::new(&[(1, 0)]),
+      Err(AocError::InvalidInput(
+        "Can't place a 2D slice in 1 dimensions".to_string()
+      ))
+    );
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    let slice = parse_input(".#.\n..#\n###").unwrap();
+    assert_eq!(solve_part1(&slice), Ok(112));
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    let slice = parse_input(".#.\n..#\n###").unwrap();
+    assert_eq!(solve_part2(&slice), Ok(848));
+  }
+}
@@ -0,0 +1,423 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::error::AocError;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+  Add,
+  Multiply,
+}
+
+impl fmt::Display for Operator {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Operator::Add => write!(f, "+"),
+      Operator::Multiply => write!(f, "*"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
+  Number(u64),
+  Op(Operator),
+  OpenParen,
+  CloseParen,
+}
+
+impl fmt::Display for Token {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Token::Number(x) => write!(f, "{}", x),
+      Token::Op(op) => write!(f, "{}", op),
+      Token::OpenParen => write!(f, "("),
+      Token::CloseParen => write!(f, ")"),
+    }
+  }
+}
+
+// the tokens of a line, each with the 1-based column it starts at
+pub type Tokens = Vec<(usize, Token)>;
+
+// errors point at the column in the line, but always at the first line
+pub fn tokenize(text: &str) -> Result<Tokens, AocError> {
+  let mut tokens = vec![];
+  let mut chars = text.chars().enumerate().peekable();
+
+  while let Some((i, c)) = chars.next() {
+    match c {
+      ' ' => {}
+      '+' => tokens.push((i + 1, Token::Op(Operator::Add))),
+      '*' => tokens.push((i + 1, Token::Op(Operator::Multiply))),
+      '(' => tokens.push((i + 1, Token::OpenParen)),
+      ')' => tokens.push((i + 1, Token::CloseParen)),
+      '0'..='9' => {
+        let mut number = c.to_digit(10).unwrap() as u64;
+        while let Some(digit) = chars.peek().and_then(|(_, next)| next.to_digit(10)) {
+          number = number
+            .checked_mul(10)
+            .and_then(|x| x.checked_add(digit as u64))
+            .ok_or_else(|| AocError::parse(1, i + 1, "Number is too large"))?;
+          chars.next();
+        }
+        tokens.push((i + 1, Token::Number(number)));
+      }
+      _ => {
+        let error_msg = format!("Unrecognized token `{}`", c);
+        return Err(AocError::parse(1, i + 1, &error_msg));
+      }
+    }
+  }
+
+  Ok(tokens)
+}
+
+// higher values bind tighter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Precedence {
+  pub add: u8,
+  pub multiply: u8,
+}
+
+impl Precedence {
+  pub const SAME: Precedence = Precedence {
+    add: 1,
+    multiply: 1,
+  };
+  pub const ADD_FIRST: Precedence = Precedence {
+    add: 2,
+    multiply: 1,
+  };
+
+  fn of(&self, op: Operator) -> u8 {
+    match op {
+      Operator::Add => self.add,
+      Operator::Multiply => self.multiply,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+  Number(u64),
+  BinaryOp(Box<Expr>, Operator, Box<Expr>),
+}
+
+impl Expr {
+  // returns `None` if the result does not fit in a u64
+  pub fn eval(&self) -> Option<u64> {
+    match self {
+      Expr::Number(x) => Some(*x),
+      Expr::BinaryOp(lhs, Operator::Add, rhs) => lhs.eval()?.checked_add(rhs.eval()?),
+      Expr::BinaryOp(lhs, Operator::Multiply, rhs) => lhs.eval()?.checked_mul(rhs.eval()?),
+    }
+  }
+}
+
+// prints the expression fully parenthesized, so the precedence is explicit
+impl fmt::Display for Expr {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Expr::Number(x) => write!(f, "{}", x),
+      Expr::BinaryOp(lhs, op, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
+    }
+  }
+}
+
+struct Parser<'a> {
+  tokens: &'a [(usize, Token)],
+  position: usize,
+  precedence: Precedence,
+  // the column right after the last token, where a missing one would go
+  end: usize,
+}
+
+// errors point at the column in the line, but always at the first line
+fn error_at(column: usize, message: &str) -> AocError {
+  AocError::parse(1, column, message)
+}
+
+fn unexpected(column: usize, token: Token) -> AocError {
+  error_at(column, &format!("Unexpected token `{}`", token))
+}
+
+impl<'a> Parser<'a> {
+  fn new(tokens: &'a [(usize, Token)], precedence: Precedence) -> Self {
+    let end = tokens
+      .last()
+      .map_or(1, |(column, token)| column + token.to_string().len());
+    Parser {
+      tokens,
+      position: 0,
+      precedence,
+      end,
+    }
+  }
+
+  fn next_token(&mut self) -> Option<(usize, Token)> {
+    let token = self.tokens.get(self.position).copied();
+    self.position += 1;
+    token
+  }
+
+  fn parse(&mut self) -> Result<Expr, AocError> {
+    let expr = self.parse_expr(0)?;
+
+    match self.next_token() {
+      None => Ok(expr),
+      Some((column, Token::CloseParen)) => Err(error_at(column, "Unbalanced parentheses")),
+      Some((column, token)) => Err(unexpected(column, token)),
+    }
+  }
+
+  // precedence climbing: only operators binding at least as tight as
+  // `min_precedence` are consumed at this level; it's wider than the
+  // precedences so the level above the tightest one still fits
+  fn parse_expr(&mut self, min_precedence: u16) -> Result<Expr, AocError> {
+    let mut lhs = self.parse_operand()?;
+
+    while let Some((_, Token::Op(op))) = self.tokens.get(self.position).copied() {
+      let precedence = u16::from(self.precedence.of(op));
+      if precedence < min_precedence {
+        break;
+      }
+
+      self.position += 1;
+      let rhs = self.parse_expr(precedence + 1)?;
+      lhs = Expr::BinaryOp(Box::new(lhs), op, Box::new(rhs));
+    }
+
+    Ok(lhs)
+  }
+
+  fn parse_operand(&mut self) -> Result<Expr, AocError> {
+    match self.next_token() {
+      Some((_, Token::Number(x))) => Ok(Expr::Number(x)),
+      Some((_, Token::OpenParen)) => {
+        let expr = self.parse_expr(0)?;
+        match self.next_token() {
+          Some((_, Token::CloseParen)) => Ok(expr),
+          Some((column, _)) => Err(error_at(column, "Unbalanced parentheses")),
+          None => Err(error_at(self.end, "Unbalanced parentheses")),
+        }
+      }
+      Some((column, token)) => Err(unexpected(column, token)),
+      None => Err(error_at(self.end, "Unexpected end of expression")),
+    }
+  }
+}
+
+pub fn parse_expr(tokens: &[(usize, Token)], precedence: Precedence) -> Result<Expr, AocError> {
+  Parser::new(tokens, precedence).parse()
+}
+
+fn sum_expressions(homework: &[Tokens], precedence: Precedence) -> Result<u64, AocError> {
+  let overflow = || AocError::Overflow("Result does not fit in 64 bits".to_string());
+
+  homework
+    .iter()
+    .enumerate()
+    .try_fold(0u64, |total, (i, tokens)| {
+      let expr = parse_expr(tokens, precedence).map_err(|err| err.at_line(i + 1))?;
+      let value = expr.eval().ok_or_else(overflow)?;
+      total.checked_add(value).ok_or_else(overflow)
+    })
+}
+
+#[aoc_generator(day18)]
+pub fn parse_input(input: &str) -> Result<Vec<Tokens>, AocError> {
+  input
+    .lines()
+    .enumerate()
+    .map(|(i, line)| tokenize(line).map_err(|err| err.at_line(i + 1)))
+    .collect()
+}
+
+#[aoc(day18, part1)]
+pub fn solve_part1(homework: &[Tokens]) -> Result<u64, AocError> {
+  sum_expressions(homework, Precedence::SAME)
+}
+
+#[aoc(day18, part2)]
+pub fn solve_part2(homework: &[Tokens]) -> Result<u64, AocError> {
+  sum_expressions(homework, Precedence::ADD_FIRST)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn eval(text: &str, precedence: Precedence) -> Result<u64, AocError> {
+    sum_expressions(&[tokenize(text)?], precedence)
+  }
+
+  #[test]
+  fn test_tokenize() {
+    assert_eq!(
+      tokenize("12 * (3 + 4)"),
+      Ok(vec![
+        (1, Token::Number(12)),
+        (4, Token::Op(Operator::Multiply)),
+        (6, Token::OpenParen),
+        (7, Token::Number(3)),
+        (9, Token::Op(Operator::Add)),
+        (11, Token::Number(4)),
+        (12, Token::CloseParen),
+      ])
+    );
+    assert_eq!(
+      tokenize("1 - 2"),
+      Err(AocError::parse(1, 3, "Unrecognized token `-`"))
+    );
+    assert!(tokenize("99999999999999999999").is_err());
+  }
+
+  #[test]
+  fn test_parse_expr() {
+    let tokens = tokenize("1 + 2 * 3").unwrap();
+    assert_eq!(
+      parse_expr(&tokens, Precedence::SAME).unwrap().to_string(),
+      "((1 + 2) * 3)"
+    );
+    assert_eq!(
+      parse_expr(&tokens, Precedence::ADD_FIRST)
+        .unwrap()
+        .to_string(),
+      "((1 + 2) * 3)"
+    );
+
+    let tokens = tokenize("2 * 3 + (4 * 5)").unwrap();
+    assert_eq!(
+      parse_expr(&tokens, Precedence::SAME).unwrap().to_string(),
+      "((2 * 3) + (4 * 5))"
+    );
+    assert_eq!(
+      parse_expr(&tokens, Precedence::ADD_FIRST)
+        .unwrap()
+        .to_string(),
+      "(2 * (3 + (4 * 5)))"
+    );
+
+    let highest = Precedence {
+      add: u8::MAX,
+      multiply: u8::MAX,
+    };
+    assert_eq!(
+      parse_expr(&tokens, highest).unwrap().to_string(),
+      "((2 * 3) + (4 * 5))"
+    );
+  }
+
+  #[test]
+  fn test_parse_expr_errors() {
+    let parse = |text| parse_expr(&tokenize(text).unwrap(), Precedence::SAME);
+
+    let error = |column, message| Err(AocError::parse(1, column, message));
+
+    assert_eq!(parse("(1 + 2"), error(7, "Unbalanced parentheses"));
+    assert_eq!(parse("(1 + 2 3)"), error(8, "Unbalanced parentheses"));
+    assert_eq!(parse("1 + 2)"), error(6, "Unbalanced parentheses"));
+    assert_eq!(parse("1 +"), error(4, "Unexpected end of expression"));
+    assert_eq!(parse(""), error(1, "Unexpected end of expression"));
+    assert_eq!(parse("1 2"), error(3, "Unexpected token `2`"));
+    assert_eq!(parse("* 2"), error(1, "Unexpected token `*`"));
+
+    let homework = parse_input("1 + 2\n3 * (4 +").unwrap();
+    assert_eq!(
+      solve_part1(&homework),
+      Err(AocError::parse(2, 9, "Unexpected end of expression"))
+    );
+  }
+
+  #[test]
+  fn test_parse_input() {
+    assert_eq!(
+      parse_input("1 + 2\n3 * x"),
+      Err(AocError::parse(2, 5, "Unrecognized token `x`"))
+    );
+  }
+
+  #[test]
+  fn test_eval_overflow() {
+    assert_eq!(
+      eval("4294967296 * 4294967296", Precedence::SAME),
+      Err(AocError::Overflow(
+        "Result does not fit in 64 bits".to_string()
+      ))
+    );
+    assert!(solve_part1(&[tokenize("1 +").unwrap()]).is_err());
+  }
+
+  #[test]
+  fn test_eval_same_precedence() {
+    assert_eq!(eval("1 + 2 * 3 + 4 * 5 + 6", Precedence::SAME), Ok(71));
+    assert_eq!(
+      eval("1 + (2 * 3) + (4 * (5 + 6))", Precedence::SAME),
+      Ok(51)
+    );
+    assert_eq!(eval("2 * 3 + (4 * 5)", Precedence::SAME), Ok(26));
+    assert_eq!(
+      eval("5 + (8 * 3 + 9 + 3 * 4 * 3)", Precedence::SAME),
+      Ok(437)
+    );
+    assert_eq!(
+      eval(
+        "5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))",
+        Precedence::SAME
+      ),
+      Ok(12240)
+    );
+    assert_eq!(
+      eval(
+        "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+        Precedence::SAME
+      ),
+      Ok(13632)
+    );
+  }
+
+  #[test]
+  fn test_eval_add_first() {
+    assert_eq!(
+      eval("1 + 2 * 3 + 4 * 5 + 6", Precedence::ADD_FIRST),
+      Ok(231)
+    );
+    assert_eq!(
+      eval("1 + (2 * 3) + (4 * (5 + 6))", Precedence::ADD_FIRST),
+      Ok(51)
+    );
+    assert_eq!(eval("2 * 3 + (4 * 5)", Precedence::ADD_FIRST), Ok(46));
+    assert_eq!(
+      eval("5 + (8 * 3 + 9 + 3 * 4 * 3)", Precedence::ADD_FIRST),
+      Ok(1445)
+    );
+    assert_eq!(
+      eval(
+        "5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))",
+        Precedence::ADD_FIRST
+      ),
+      Ok(669060)
+    );
+    assert_eq!(
+      eval(
+        "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+        Precedence::ADD_FIRST
+      ),
+      Ok(23340)
+    );
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    let homework = parse_input("2 * 3 + (4 * 5)\n5 + (8 * 3 + 9 + 3 * 4 * 3)").unwrap();
+    assert_eq!(solve_part1(&homework), Ok(26 + 437));
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    let homework = parse_input("2 * 3 + (4 * 5)\n5 + (8 * 3 + 9 + 3 * 4 * 3)").unwrap();
+    assert_eq!(solve_part2(&homework), Ok(46 + 1445));
+  }
+}
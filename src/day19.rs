@@ -0,0 +1,368 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::error::{blocks, parse_lines, AocError};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+  Char(char),
+  Alternatives(Vec<Vec<usize>>), // each alternative is a sequence of rule ids
+}
+
+impl FromStr for Rule {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let text = text.trim();
+    let error_msg = format!("Error parsing rule `{}`", text);
+
+    if text.starts_with('"') {
+      let mut chars = text.trim_matches('"').chars();
+      return match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Rule::Char(c)),
+        _ => Err(error_msg),
+      };
+    }
+
+    let alternatives = text
+      .split('|')
+      .map(|sequence| {
+        sequence
+          .split_whitespace()
+          .map(|id| id.parse::<usize>())
+          .collect::<Result<Vec<usize>, _>>()
+      })
+      .collect::<Result<Vec<Vec<usize>>, _>>()
+      .or(Err(error_msg.clone()))?;
+
+    if alternatives.iter().any(|sequence| sequence.is_empty()) {
+      return Err(error_msg);
+    }
+
+    Ok(Rule::Alternatives(alternatives))
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grammar {
+  rules: HashMap<usize, Rule>,
+}
+
+// a line like `3: 4 5 | 5 4`
+fn parse_rule_line(line: &str) -> Result<(usize, Rule), String> {
+  let mut chunks = line.split(':');
+  let id = chunks
+    .next()
+    .and_then(|id| id.trim().parse::<usize>().ok())
+    .ok_or(format!("Error parsing rule id in `{}`", line))?;
+  let rule = chunks
+    .next()
+    .ok_or(format!("Error parsing `{}`", line))?
+    .parse::<Rule>()?;
+  Ok((id, rule))
+}
+
+impl FromStr for Grammar {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let rules = text
+      .lines()
+      .map(parse_rule_line)
+      .collect::<Result<HashMap<usize, Rule>, String>>()?;
+
+    Ok(Grammar { rules })
+  }
+}
+
+impl Grammar {
+  pub fn get_rule(&self, id: usize) -> Option<&Rule> {
+    self.rules.get(&id)
+  }
+
+  pub fn set_rule(&mut self, id: usize, rule: Rule) {
+    self.rules.insert(id, rule);
+  }
+
+  // returns every possible amount of chars that the rule can consume from
+  // the start of the text
+  pub fn match_lengths(&self, id: usize, text: &[char]) -> Result<BTreeSet<usize>, AocError> {
+    self.lengths(id, text, &mut HashSet::new())
+  }
+
+  // `in_progress` has the rules being matched, with the length of the text
+  // they're matching; running into one of them again means the rule would
+  // recurse forever without consuming any char, like `8: 8 42` does
+  fn lengths(
+    &self,
+    id: usize,
+    text: &[char],
+    in_progress: &mut HashSet<(usize, usize)>,
+  ) -> Result<BTreeSet<usize>, AocError> {
+    let rule = self
+      .get_rule(id)
+      .ok_or_else(|| AocError::InvalidInput(format!("Rule #{} does not exist", id)))?;
+
+    if !in_progress.insert((id, text.len())) {
+      let error_msg = format!("Rule #{} recurses without consuming any char", id);
+      return Err(AocError::InvalidInput(error_msg));
+    }
+    let result = self.rule_lengths(rule, text, in_progress);
+    in_progress.remove(&(id, text.len()));
+
+    result
+  }
+
+  fn rule_lengths(
+    &self,
+    rule: &Rule,
+    text: &[char],
+    in_progress: &mut HashSet<(usize, usize)>,
+  ) -> Result<BTreeSet<usize>, AocError> {
+    match rule {
+      Rule::Char(c) => Ok(if text.first() == Some(c) {
+        [1].iter().copied().collect()
+      } else {
+        BTreeSet::new()
+      }),
+      Rule::Alternatives(alternatives) => {
+        let mut result = BTreeSet::new();
+
+        for sequence in alternatives {
+          let mut partials: BTreeSet<usize> = [0].iter().copied().collect();
+          for sub_id in sequence {
+            let mut next_partials = BTreeSet::new();
+            for start in partials {
+              for length in self.lengths(*sub_id, &text[start..], in_progress)? {
+                next_partials.insert(start + length);
+              }
+            }
+            partials = next_partials;
+          }
+          result.extend(partials);
+        }
+
+        Ok(result)
+      }
+    }
+  }
+
+  pub fn is_match(&self, message: &str) -> Result<bool, AocError> {
+    let text: Vec<char> = message.chars().collect();
+    Ok(self.match_lengths(0, &text)?.contains(&text.len()))
+  }
+}
+
+fn count_matches(grammar: &Grammar, messages: &[String]) -> Result<usize, AocError> {
+  let mut count = 0;
+
+  for message in messages {
+    if grammar.is_match(message)? {
+      count += 1;
+    }
+  }
+
+  Ok(count)
+}
+
+#[aoc_generator(day19)]
+pub fn parse_input(input: &str) -> Result<(Grammar, Vec<String>), AocError> {
+  let mut sections = blocks(input);
+
+  let (_, raw_rules) = sections
+    .next()
+    .ok_or_else(|| AocError::InvalidInput("No rules present".to_string()))?;
+  let rules = parse_lines(raw_rules, parse_rule_line)?;
+  let grammar = Grammar {
+    rules: rules.into_iter().collect(),
+  };
+
+  let (_, raw_messages) = sections
+    .next()
+    .ok_or_else(|| AocError::InvalidInput("No messages present".to_string()))?;
+  let messages = raw_messages.lines().map(|x| x.to_string()).collect();
+
+  Ok((grammar, messages))
+}
+
+#[aoc(day19, part1)]
+pub fn solve_part1(input: &(Grammar, Vec<String>)) -> Result<usize, AocError> {
+  let (grammar, messages) = input;
+  count_matches(grammar, messages)
+}
+
+#[aoc(day19, part2)]
+pub fn solve_part2(input: &(Grammar, Vec<String>)) -> Result<usize, AocError> {
+  let (grammar, messages) = input;
+
+  let mut grammar = grammar.clone();
+  grammar.set_rule(8, Rule::Alternatives(vec![vec![42], vec![42, 8]]));
+  grammar.set_rule(11, Rule::Alternatives(vec![vec![42, 31], vec![42, 11, 31]]));
+
+  count_matches(&grammar, messages)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EXAMPLE: &str = "0: 4 1 5
+1: 2 3 | 3 2
+2: 4 4 | 5 5
+3: 4 5 | 5 4
+4: \"a\"
+5: \"b\"
+
+ababbb
+bababa
+abbbab
+aaabbb
+aaaabbb";
+
+  const RECURSIVE_EXAMPLE: &str = "42: 9 14 | 10 1
+9: 14 27 | 1 26
+10: 23 14 | 28 1
+1: \"a\"
+11: 42 31
+5: 1 14 | 15 1
+19: 14 1 | 14 14
+12: 24 14 | 19 1
+16: 15 1 | 14 14
+31: 14 17 | 1 13
+6: 14 14 | 1 14
+2: 1 24 | 14 4
+0: 8 11
+13: 14 3 | 1 12
+15: 1 | 14
+17: 14 2 | 1 7
+23: 25 1 | 22 14
+28: 16 1
+4: 1 1
+20: 14 14 | 1 15
+3: 5 14 | 16 1
+27: 1 6 | 14 18
+14: \"b\"
+21: 14 1 | 1 14
+25: 1 1 | 1 14
+22: 14 14
+8: 42
+26: 14 22 | 1 20
+18: 15 15
+7: 14 5 | 1 21
+24: 14 1
+
+abbbbbabbbaaaababbaabbbbabababbbabbbbbbabaaaa
+bbabbbbaabaabba
+babbbbaabbbbbabbbbbbaabaaabaaa
+aaabbbbbbaaaabaababaabababbabaaabbababababaaa
+bbbbbbbaaaabbbbaaabbabaaa
+bbbababbbbaaaaaaaabbababaaababaabab
+ababaaaaaabaaab
+ababaaaaabbbaba
+baabbaaaabbaaaababbaababb
+abbbbabbbbaaaababbbbbbaaaababb
+aaaaabbaabaaaaababaa
+aaaabbaaaabbaaa
+aaaabbaabbaaaaaaabbbabbbaaabbaabaaa
+babaaabbbaaabaababbaabababaaab
+aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba";
+
+  #[test]
+  fn test_rule_from_str() {
+    assert_eq!("\"a\"".parse::<Rule>(), Ok(Rule::Char('a')));
+    assert_eq!(
+      " 4 1 5".parse::<Rule>(),
+      Ok(Rule::Alternatives(vec![vec![4, 1, 5]]))
+    );
+    assert_eq!(
+      "2 3 | 3 2".parse::<Rule>(),
+      Ok(Rule::Alternatives(vec![vec![2, 3], vec![3, 2]]))
+    );
+    assert!("\"ab\"".parse::<Rule>().is_err());
+    assert!("1 | ".parse::<Rule>().is_err());
+    assert!("1 a".parse::<Rule>().is_err());
+  }
+
+  #[test]
+  fn test_parse_input() {
+    let (grammar, messages) = parse_input(EXAMPLE).unwrap();
+    assert_eq!(grammar.get_rule(4), Some(&Rule::Char('a')));
+    assert_eq!(
+      grammar.get_rule(1),
+      Some(&Rule::Alternatives(vec![vec![2, 3], vec![3, 2]]))
+    );
+    assert_eq!(messages.len(), 5);
+    assert_eq!(messages[0], "ababbb");
+
+    assert_eq!(
+      parse_input("0: 1\n1: a\n\na").err(),
+      Some(AocError::parse(2, 1, "Error parsing rule `a`"))
+    );
+    assert!(parse_input("0: \"a\"").is_err());
+  }
+
+  #[test]
+  fn test_match_lengths() {
+    let grammar: Grammar = "0: 1 | 1 0\n1: \"a\"".parse().unwrap();
+    let text: Vec<char> = "aab".chars().collect();
+    assert_eq!(
+      grammar.match_lengths(0, &text),
+      Ok([1, 2].iter().copied().collect())
+    );
+    assert!(grammar.match_lengths(2, &text).is_err());
+  }
+
+  #[test]
+  fn test_left_recursion() {
+    let (mut grammar, _) = parse_input(RECURSIVE_EXAMPLE).unwrap();
+    grammar.set_rule(8, "8 42".parse().unwrap());
+    assert_eq!(
+      grammar.is_match("bbabbbbaabaabba"),
+      Err(AocError::InvalidInput(
+        "Rule #8 recurses without consuming any char".to_string()
+      ))
+    );
+
+    let grammar: Grammar = "0: 0 1\n1: \"a\"".parse().unwrap();
+    assert!(grammar.is_match("aa").is_err());
+  }
+
+  #[test]
+  fn test_empty_alternative() {
+    // `8: 42 |` can't be parsed, but it can be set
+    let mut grammar: Grammar = "0: 8 1\n1: \"a\"\n42: \"b\"".parse().unwrap();
+    grammar.set_rule(8, Rule::Alternatives(vec![vec![42], vec![]]));
+    assert_eq!(grammar.is_match("ba"), Ok(true));
+    assert_eq!(grammar.is_match("a"), Ok(true));
+    assert_eq!(grammar.is_match("bba"), Ok(false));
+
+    grammar.set_rule(8, Rule::Alternatives(vec![vec![], vec![8, 42]]));
+    assert!(grammar.is_match("ba").is_err());
+  }
+
+  #[test]
+  fn test_is_match() {
+    let (grammar, _) = parse_input(EXAMPLE).unwrap();
+    assert_eq!(grammar.is_match("ababbb"), Ok(true));
+    assert_eq!(grammar.is_match("abbbab"), Ok(true));
+    assert_eq!(grammar.is_match("bababa"), Ok(false));
+    assert_eq!(grammar.is_match("aaabbb"), Ok(false));
+    assert_eq!(grammar.is_match("aaaabbb"), Ok(false));
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    assert_eq!(solve_part1(&parse_input(EXAMPLE).unwrap()), Ok(2));
+    assert_eq!(solve_part1(&parse_input(RECURSIVE_EXAMPLE).unwrap()), Ok(3));
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    assert_eq!(
+      solve_part2(&parse_input(RECURSIVE_EXAMPLE).unwrap()),
+      Ok(12)
+    );
+  }
+}
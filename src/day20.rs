@@ -0,0 +1,575 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::error::{blocks, AocError};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+const SEA_MONSTER: &str = "                  # \n#    ##    ##    ###\n #  #  #  #  #  #   ";
+
+// a square grid of pixels, where `true` is a `#`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+  pixels: Vec<bool>,
+  size: usize,
+}
+
+impl FromStr for Image {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let mut pixels = vec![];
+    let height = text.lines().count();
+
+    if height == 0 {
+      return Err("Images can't be empty".to_string());
+    }
+    for line in text.lines() {
+      if line.chars().count() != height {
+        return Err("Images must be square".to_string());
+      }
+      for c in line.chars() {
+        match c {
+          '#' => pixels.push(true),
+          '.' => pixels.push(false),
+          _ => return Err(format!("Unrecognized pixel {:?}", c)),
+        }
+      }
+    }
+
+    Ok(Image {
+      pixels,
+      size: height,
+    })
+  }
+}
+
+impl fmt::Display for Image {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for row in self.pixels.chunks(self.size) {
+      let line: String = row.iter().map(|&x| if x { '#' } else { '.' }).collect();
+      writeln!(f, "{}", line)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl Image {
+  fn from_fn(size: usize, pixel_at: impl Fn(usize, usize) -> bool) -> Self {
+    let pixels = (0..size * size)
+      .map(|i| pixel_at(i % size, i / size))
+      .collect();
+    Image { pixels, size }
+  }
+
+  pub fn size(&self) -> usize {
+    self.size
+  }
+
+  pub fn get(&self, x: usize, y: usize) -> bool {
+    self.pixels[x + y * self.size]
+  }
+
+  pub fn count_filled(&self) -> usize {
+    self.pixels.iter().filter(|&&x| x).count()
+  }
+
+  // images built by `strip_borders` may be empty, so this can't underflow
+  fn last(&self) -> usize {
+    self.size.saturating_sub(1)
+  }
+
+  // 90º clockwise
+  pub fn rotate(&self) -> Self {
+    let last = self.last();
+    Image::from_fn(self.size, |x, y| self.get(y, last - x))
+  }
+
+  // mirror along the vertical axis
+  pub fn flip(&self) -> Self {
+    let last = self.last();
+    Image::from_fn(self.size, |x, y| self.get(last - x, y))
+  }
+
+  pub fn orientations(&self) -> Vec<Self> {
+    let mut result = vec![];
+    let mut image = self.clone();
+
+    for _ in 0..4 {
+      result.push(image.flip());
+      image = image.rotate();
+      result.push(image.clone());
+    }
+
+    result
+  }
+
+  pub fn top(&self) -> Vec<bool> {
+    (0..self.size).map(|x| self.get(x, 0)).collect()
+  }
+
+  pub fn bottom(&self) -> Vec<bool> {
+    (0..self.size).map(|x| self.get(x, self.last())).collect()
+  }
+
+  pub fn left(&self) -> Vec<bool> {
+    (0..self.size).map(|y| self.get(0, y)).collect()
+  }
+
+  pub fn right(&self) -> Vec<bool> {
+    (0..self.size).map(|y| self.get(self.last(), y)).collect()
+  }
+
+  pub fn borders(&self) -> [Vec<bool>; 4] {
+    [self.top(), self.right(), self.bottom(), self.left()]
+  }
+
+  pub fn strip_borders(&self) -> Self {
+    Image::from_fn(self.size.saturating_sub(2), |x, y| self.get(x + 1, y + 1))
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+  id: u64,
+  image: Image,
+}
+
+impl FromStr for Tile {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let error_msg = format!("Error parsing tile `{}`", text);
+    let mut lines = text.splitn(2, '\n');
+
+    let id = lines
+      .next()
+      .and_then(|header| header.strip_prefix("Tile "))
+      .and_then(|header| header.strip_suffix(':'))
+      .and_then(|id| id.parse::<u64>().ok())
+      .ok_or(error_msg.clone())?;
+    let image = lines.next().ok_or(error_msg)?.parse::<Image>()?;
+
+    Ok(Tile { id, image })
+  }
+}
+
+impl Tile {
+  pub fn orientations(&self) -> Vec<Self> {
+    self
+      .image
+      .orientations()
+      .into_iter()
+      .map(|image| Tile { id: self.id, image })
+      .collect()
+  }
+
+  // amount of borders that fit some border of any of the other tiles
+  fn count_matching_borders(&self, tiles: &[Tile]) -> usize {
+    let other_borders: Vec<Vec<bool>> = tiles
+      .iter()
+      .filter(|tile| tile.id != self.id)
+      .flat_map(|tile| tile.image.borders().to_vec())
+      .collect();
+
+    self
+      .image
+      .borders()
+      .iter()
+      .filter(|border| {
+        let reversed: Vec<bool> = border.iter().rev().copied().collect();
+        other_borders
+          .iter()
+          .any(|other| other == *border || *other == reversed)
+      })
+      .count()
+  }
+}
+
+fn find_corners(tiles: &[Tile]) -> Vec<&Tile> {
+  tiles
+    .iter()
+    .filter(|tile| tile.count_matching_borders(tiles) == 2)
+    .collect()
+}
+
+fn get_puzzle_side(tiles: &[Tile]) -> Result<usize, AocError> {
+  let side = (tiles.len() as f64).sqrt() as usize;
+  if side * side != tiles.len() || side == 0 {
+    let error_msg = format!("Cannot build a square with {} tiles", tiles.len());
+    return Err(AocError::InvalidInput(error_msg));
+  }
+
+  Ok(side)
+}
+
+// places the tiles in row-major order, backtracking when a tile doesn't fit
+fn place_tiles(tiles: &[Tile], side: usize, placed: &mut Vec<Tile>) -> bool {
+  if placed.len() == tiles.len() {
+    return true;
+  }
+
+  let position = placed.len();
+  let (x, y) = (position % side, position / side);
+
+  for tile in tiles.iter() {
+    if placed.iter().any(|other| other.id == tile.id) {
+      continue;
+    }
+
+    for candidate in tile.orientations() {
+      let fits_left = x == 0 || placed[position - 1].image.right() == candidate.image.left();
+      let fits_top = y == 0 || placed[position - side].image.bottom() == candidate.image.top();
+
+      if fits_left && fits_top {
+        placed.push(candidate);
+        if place_tiles(tiles, side, placed) {
+          return true;
+        }
+        placed.pop();
+      }
+    }
+  }
+
+  false
+}
+
+pub fn assemble_image(tiles: &[Tile]) -> Result<Image, AocError> {
+  let side = get_puzzle_side(tiles)?;
+  if tiles.iter().any(|tile| tile.image.size() < 3) {
+    return Err(AocError::InvalidInput(
+      "Tiles are too small to strip their borders".to_string(),
+    ));
+  }
+  if tiles
+    .iter()
+    .any(|tile| tile.image.size() != tiles[0].image.size())
+  {
+    return Err(AocError::InvalidInput(
+      "Tiles must all have the same size".to_string(),
+    ));
+  }
+
+  let mut placed = vec![];
+  if !place_tiles(tiles, side, &mut placed) {
+    return Err(AocError::NoSolution(
+      "Tiles could not be assembled".to_string(),
+    ));
+  }
+
+  let stripped: Vec<Image> = placed
+    .iter()
+    .map(|tile| tile.image.strip_borders())
+    .collect();
+  let tile_size = stripped[0].size();
+
+  Ok(Image::from_fn(side * tile_size, |x, y| {
+    let tile = &stripped[x / tile_size + (y / tile_size) * side];
+    tile.get(x % tile_size, y % tile_size)
+  }))
+}
+
+fn parse_pattern(pattern: &str) -> Vec<(usize, usize)> {
+  pattern
+    .lines()
+    .enumerate()
+    .flat_map(|(y, line)| {
+      line
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| *c == '#')
+        .map(move |(x, _)| (x, y))
+    })
+    .collect()
+}
+
+// returns the pixels covered by every occurrence of the pattern
+pub fn find_pattern(image: &Image, pattern: &[(usize, usize)]) -> HashSet<(usize, usize)> {
+  let width = pattern.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+  let height = pattern.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+  let mut result = HashSet::new();
+
+  if width > image.size() || height > image.size() {
+    return result;
+  }
+
+  for y in 0..=(image.size() - height) {
+    for x in 0..=(image.size() - width) {
+      if pattern.iter().all(|(dx, dy)| image.get(x + dx, y + dy)) {
+        result.extend(pattern.iter().map(|(dx, dy)| (x + dx, y + dy)));
+      }
+    }
+  }
+
+  result
+}
+
+#[aoc_generator(day20)]
+pub fn parse_input(input: &str) -> Result<Vec<Tile>, AocError> {
+  blocks(input)
+    .filter(|(_, chunk)| !chunk.trim().is_empty())
+    .map(|(line, chunk)| {
+      chunk
+        .trim()
+        .parse::<Tile>()
+        .map_err(|err| AocError::parse(line, 1, &err))
+    })
+    .collect()
+}
+
+#[aoc(day20, part1)]
+pub fn solve_part1(tiles: &[Tile]) -> Result<u64, AocError> {
+  let corners = find_corners(tiles);
+  if corners.len() != 4 {
+    let error_msg = format!("Expected 4 corners, found {}", corners.len());
+    return Err(AocError::NoSolution(error_msg));
+  }
+
+  corners
+    .iter()
+    .try_fold(1u64, |product, tile| product.checked_mul(tile.id))
+    .ok_or_else(|| AocError::Overflow("Product of corner IDs does not fit in 64 bits".to_string()))
+}
+
+#[aoc(day20, part2)]
+pub fn solve_part2(tiles: &[Tile]) -> Result<usize, AocError> {
+  let image = assemble_image(tiles)?;
+  let monster = parse_pattern(SEA_MONSTER);
+
+  image
+    .orientations()
+    .iter()
+    .map(|oriented| find_pattern(oriented, &monster).len())
+    .find(|&covered| covered > 0)
+    .map(|covered| image.count_filled() - covered)
+    .ok_or_else(|| AocError::NoSolution("No sea monsters found".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EXAMPLE: &str = "Tile 2311:
+..##.#..#.
+##..#.....
+#...##..#.
+####.#...#
+##.##.###.
+##...#.###
+.#.#.#..##
+..#....#..
+###...#.#.
+..###..###
+
+Tile 1951:
+#.##...##.
+#.####...#
+.....#..##
+#...######
+.##.#....#
+.###.#####
+###.##.##.
+.###....#.
+..#.#..#.#
+#...##.#..
+
+Tile 1171:
+####...##.
+#..##.#..#
+##.#..#.#.
+.###.####.
+..###.####
+.##....##.
+.#...####.
+#.##.####.
+####..#...
+.....##...
+
+Tile 1427:
+###.##.#..
+.#..#.##..
+.#.##.#..#
+#.#.#.##.#
+....#...##
+...##..##.
+...#.#####
+.#.####.#.
+..#..###.#
+..##.#..#.
+
+Tile 1489:
+##.#.#....
+..##...#..
+.##..##...
+..#...#...
+#####...#.
+#..#.#.#.#
+...#.#.#..
+##.#...##.
+..##.##.##
+###.##.#..
+
+Tile 2473:
+#....####.
+#..#.##...
+#.##..#...
+######.#.#
+.#...#.#.#
+.#########
+.###.#..#.
+########.#
+##...##.#.
+..###.#.#.
+
+Tile 2971:
+..#.#....#
+#...###...
+#.#.###...
+##.##..#..
+.#####..##
+.#..####.#
+#..#.#..#.
+..####.###
+..#.#.###.
+...#.#.#.#
+
+Tile 2729:
+...#.#.#.#
+####.#....
+..#.#.....
+....#..#.#
+.##..##.#.
+.#.####...
+####.#.#..
+##.####...
+##..#.##..
+#.##...##.
+
+Tile 3079:
+#.#.#####.
+.#..######
+..#.......
+######....
+####.#..#.
+.#...#.##.
+#.#####.##
+..#.###...
+..#.......
+..#.###...
+";
+
+  #[test]
+  fn test_image_from_str() {
+    let image: Image = "#.\n.#".parse().unwrap();
+    assert_eq!(image.size(), 2);
+    assert_eq!(image.pixels, vec![true, false, false, true]);
+
+    assert!("#.\n.".parse::<Image>().is_err());
+    assert!("#.\n.?".parse::<Image>().is_err());
+    assert_eq!(
+      "#..\n.".parse::<Image>(),
+      Err("Images must be square".to_string())
+    );
+    assert_eq!(
+      "".parse::<Image>(),
+      Err("Images can't be empty".to_string())
+    );
+  }
+
+  #[test]
+  fn test_image_display() {
+    let image: Image = "#..\n.#.\n##.".parse().unwrap();
+    assert_eq!(image.to_string(), "#..\n.#.\n##.\n");
+  }
+
+  #[test]
+  fn test_image_transforms() {
+    let image: Image = "#..\n.#.\n##.".parse().unwrap();
+    assert_eq!(image.rotate().to_string(), "#.#\n##.\n...\n");
+    assert_eq!(image.flip().to_string(), "..#\n.#.\n.##\n");
+    assert_eq!(image.rotate().rotate().rotate().rotate(), image);
+
+    let orientations = image.orientations();
+    assert_eq!(orientations.len(), 8);
+    for (i, a) in orientations.iter().enumerate() {
+      for b in orientations[i + 1..].iter() {
+        assert_ne!(a, b);
+      }
+    }
+  }
+
+  #[test]
+  fn test_image_borders() {
+    let image: Image = "#..\n.#.\n##.".parse().unwrap();
+    assert_eq!(image.top(), vec![true, false, false]);
+    assert_eq!(image.right(), vec![false, false, false]);
+    assert_eq!(image.bottom(), vec![true, true, false]);
+    assert_eq!(image.left(), vec![true, false, true]);
+    assert_eq!(image.strip_borders().to_string(), "#\n");
+  }
+
+  #[test]
+  fn test_tile_from_str() {
+    let tile: Tile = "Tile 42:\n#.\n.#".parse().unwrap();
+    assert_eq!(tile.id, 42);
+    assert_eq!(tile.image, "#.\n.#".parse().unwrap());
+
+    assert!("Tile x:\n#.\n.#".parse::<Tile>().is_err());
+    assert!("42:\n#.\n.#".parse::<Tile>().is_err());
+    assert!("Tile 1:\n".parse::<Tile>().is_err());
+    assert!(parse_input("Tile 1:\n").is_err());
+
+    // stripping the borders of the smallest tile leaves an empty image
+    let image = "#".parse::<Image>().unwrap().strip_borders();
+    assert_eq!(image.size(), 0);
+    assert_eq!(image.rotate().flip().top(), vec![]);
+  }
+
+  #[test]
+  fn test_parse_input_errors() {
+    let result = parse_input("Tile 1:\n#.\n.#\n\nTile 2:\n#.\n.?");
+    assert!(matches!(result, Err(AocError::Parse { line: 5, .. })));
+  }
+
+  #[test]
+  fn test_find_corners() {
+    let tiles = parse_input(EXAMPLE).unwrap();
+    let mut ids: Vec<u64> = find_corners(&tiles).iter().map(|tile| tile.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1171, 1951, 2971, 3079]);
+  }
+
+  #[test]
+  fn test_assemble_image() {
+    let tiles = parse_input(EXAMPLE).unwrap();
+    let image = assemble_image(&tiles).unwrap();
+    assert_eq!(image.size(), 24);
+
+    assert!(assemble_image(&tiles[..8]).is_err());
+  }
+
+  #[test]
+  fn test_find_pattern() {
+    let image: Image = "##.\n.##\n..#".parse().unwrap();
+    let found = find_pattern(&image, &parse_pattern("##\n.#"));
+    let expected: HashSet<(usize, usize)> = [(0, 0), (1, 0), (1, 1), (2, 1), (2, 2)]
+      .iter()
+      .copied()
+      .collect();
+    assert_eq!(found, expected);
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    let tiles = parse_input(EXAMPLE).unwrap();
+    assert_eq!(solve_part1(&tiles), Ok(20899048083289));
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    let tiles = parse_input(EXAMPLE).unwrap();
+    assert_eq!(solve_part2(&tiles), Ok(273));
+  }
+}
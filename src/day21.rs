@@ -0,0 +1,242 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::assignment::{resolve_unique, AssignmentError};
+use crate::error::AocError;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Food {
+  ingredients: BTreeSet<String>,
+  allergens: BTreeSet<String>,
+}
+
+// a comma-separated list of names, the first one starting at `column`
+fn parse_allergens(list: &str, column: usize) -> Result<BTreeSet<String>, AocError> {
+  let mut allergens = BTreeSet::new();
+  let mut column = column;
+
+  for entry in list.split(',') {
+    let name = entry.trim();
+    let name_column = column + entry.chars().take_while(|c| c.is_whitespace()).count();
+    if name.is_empty() {
+      return Err(AocError::parse(1, name_column, "Empty allergen name"));
+    }
+    if name.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+      let error_msg = format!("Invalid allergen `{}`", name);
+      return Err(AocError::parse(1, name_column, &error_msg));
+    }
+    allergens.insert(name.to_string());
+    column += entry.chars().count() + 1;
+  }
+
+  Ok(allergens)
+}
+
+// errors point at the column in the line, but always at the first line
+impl FromStr for Food {
+  type Err = AocError;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    const ALLERGENS_START: &str = "(contains ";
+
+    let text = text.trim_end();
+    let (ingredients, allergens) = match text.find(ALLERGENS_START) {
+      Some(start) => (&text[..start], Some(&text[start + ALLERGENS_START.len()..])),
+      None => (text, None),
+    };
+
+    let ingredients: BTreeSet<String> = ingredients
+      .split_whitespace()
+      .map(|x| x.to_string())
+      .collect();
+    if ingredients.is_empty() {
+      let error_msg = format!("Error parsing food `{}`", text);
+      return Err(AocError::parse(1, 1, &error_msg));
+    }
+
+    let allergens = match allergens {
+      Some(list) => {
+        let list = list.strip_suffix(')').ok_or_else(|| {
+          let column = text.chars().count() + 1;
+          AocError::parse(1, column, "Missing `)` after the allergens")
+        })?;
+        let column = text.chars().count() - list.chars().count();
+        parse_allergens(list, column)?
+      }
+      None => BTreeSet::new(),
+    };
+
+    Ok(Food {
+      ingredients,
+      allergens,
+    })
+  }
+}
+
+// for each allergen, the ingredients present in every food that lists it
+fn find_candidates(foods: &[Food]) -> BTreeMap<String, BTreeSet<String>> {
+  let mut candidates: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+  for food in foods {
+    for allergen in food.allergens.iter() {
+      let ingredients = candidates
+        .entry(allergen.to_string())
+        .or_insert_with(|| food.ingredients.clone());
+      *ingredients = ingredients
+        .intersection(&food.ingredients)
+        .cloned()
+        .collect();
+    }
+  }
+
+  candidates
+}
+
+pub fn find_safe_ingredients(foods: &[Food]) -> BTreeSet<String> {
+  let unsafe_ingredients: BTreeSet<String> =
+    find_candidates(foods).values().flatten().cloned().collect();
+
+  foods
+    .iter()
+    .flat_map(|food| food.ingredients.iter())
+    .filter(|ingredient| !unsafe_ingredients.contains(*ingredient))
+    .cloned()
+    .collect()
+}
+
+// returns the ingredient that contains each allergen
+pub fn resolve_allergens(foods: &[Food]) -> Result<BTreeMap<String, String>, AocError> {
+  resolve_unique(&find_candidates(foods)).map_err(|err| {
+    AocError::NoSolution(match err {
+      AssignmentError::Ambiguous(allergen) => {
+        format!("Ambiguous ingredient for allergen `{}`", allergen)
+      }
+      AssignmentError::Impossible(allergen) => {
+        format!("No ingredient can contain allergen `{}`", allergen)
+      }
+    })
+  })
+}
+
+#[aoc_generator(day21)]
+pub fn parse_input(input: &str) -> Result<Vec<Food>, AocError> {
+  input
+    .lines()
+    .enumerate()
+    .map(|(i, line)| line.parse::<Food>().map_err(|err| err.at_line(i + 1)))
+    .collect()
+}
+
+#[aoc(day21, part1)]
+pub fn solve_part1(foods: &[Food]) -> usize {
+  let safe = find_safe_ingredients(foods);
+
+  foods
+    .iter()
+    .map(|food| food.ingredients.intersection(&safe).count())
+    .sum()
+}
+
+#[aoc(day21, part2)]
+pub fn solve_part2(foods: &[Food]) -> Result<String, AocError> {
+  let allergens = resolve_allergens(foods)?;
+
+  // the map is already sorted by allergen
+  Ok(
+    allergens
+      .values()
+      .map(|x| x.as_str())
+      .collect::<Vec<&str>>()
+      .join(","),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EXAMPLE: &str = "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
+trh fvjkl sbzzf mxmxvkd (contains dairy)
+sqjhc fvjkl (contains soy)
+sqjhc mxmxvkd sbzzf (contains fish)";
+
+  fn to_set(list: &[&str]) -> BTreeSet<String> {
+    list.iter().map(|x| x.to_string()).collect()
+  }
+
+  #[test]
+  fn test_food_from_str() {
+    assert_eq!(
+      "sqjhc fvjkl (contains soy, dairy)".parse::<Food>(),
+      Ok(Food {
+        ingredients: to_set(&["sqjhc", "fvjkl"]),
+        allergens: to_set(&["soy", "dairy"]),
+      })
+    );
+    assert_eq!(
+      "sqjhc fvjkl".parse::<Food>(),
+      Ok(Food {
+        ingredients: to_set(&["sqjhc", "fvjkl"]),
+        allergens: BTreeSet::new(),
+      })
+    );
+    assert!("(contains soy)".parse::<Food>().is_err());
+    assert_eq!(
+      "a b (contains dairy".parse::<Food>(),
+      Err(AocError::parse(1, 20, "Missing `)` after the allergens"))
+    );
+    assert_eq!(
+      "a b (contains dairy, )".parse::<Food>(),
+      Err(AocError::parse(1, 22, "Empty allergen name"))
+    );
+    assert_eq!(
+      "a b (contains ,dairy)".parse::<Food>(),
+      Err(AocError::parse(1, 15, "Empty allergen name"))
+    );
+    assert_eq!(
+      "a (contains x) (contains y)".parse::<Food>(),
+      Err(AocError::parse(1, 13, "Invalid allergen `x) (contains y`"))
+    );
+    assert_eq!(
+      parse_input("a b (contains soy)\n(contains soy)"),
+      Err(AocError::parse(2, 1, "Error parsing food `(contains soy)`"))
+    );
+  }
+
+  #[test]
+  fn test_find_safe_ingredients() {
+    let foods = parse_input(EXAMPLE).unwrap();
+    assert_eq!(
+      find_safe_ingredients(&foods),
+      to_set(&["kfcds", "nhms", "sbzzf", "trh"])
+    );
+  }
+
+  #[test]
+  fn test_resolve_allergens() {
+    let foods = parse_input(EXAMPLE).unwrap();
+    let expected: BTreeMap<String, String> =
+      [("dairy", "mxmxvkd"), ("fish", "sqjhc"), ("soy", "fvjkl")]
+        .iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect();
+    assert_eq!(resolve_allergens(&foods), Ok(expected));
+
+    let foods = parse_input("a b (contains dairy, fish)").unwrap();
+    assert!(resolve_allergens(&foods).is_err());
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    let foods = parse_input(EXAMPLE).unwrap();
+    assert_eq!(solve_part1(&foods), 5);
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    let foods = parse_input(EXAMPLE).unwrap();
+    assert_eq!(solve_part2(&foods), Ok("mxmxvkd,sqjhc,fvjkl".to_string()));
+  }
+}
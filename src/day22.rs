@@ -0,0 +1,299 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::error::{blocks, AocError};
+use std::collections::{HashSet, VecDeque};
+
+pub type Deck = VecDeque<u64>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Player {
+  One,
+  Two,
+}
+
+impl Player {
+  fn index(self) -> usize {
+    match self {
+      Player::One => 0,
+      Player::Two => 1,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rules {
+  Normal,
+  Recursive,
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+  decks: [Deck; 2],
+  rules: Rules,
+  round: usize,
+  winner: Option<Player>,
+  history: HashSet<[Deck; 2]>,
+}
+
+impl Game {
+  pub fn new(player1: &Deck, player2: &Deck, rules: Rules) -> Self {
+    Game {
+      decks: [player1.clone(), player2.clone()],
+      rules,
+      round: 0,
+      winner: None,
+      history: HashSet::new(),
+    }
+  }
+
+  pub fn deck(&self, player: Player) -> &Deck {
+    &self.decks[player.index()]
+  }
+
+  pub fn round(&self) -> usize {
+    self.round
+  }
+
+  pub fn winner(&self) -> Option<Player> {
+    self.winner
+  }
+
+  pub fn score(&self, player: Player) -> u64 {
+    self
+      .deck(player)
+      .iter()
+      .rev()
+      .enumerate()
+      .map(|(i, card)| (i as u64 + 1) * card)
+      .sum()
+  }
+
+  // plays a single round and returns who won it, or `None` if the game is
+  // over; a normal game that repeats a previous state would never end, so
+  // that's an error, and the game is left as it was
+  pub fn step(&mut self) -> Result<Option<Player>, AocError> {
+    self.update_winner();
+    if self.winner.is_some() {
+      return Ok(None);
+    }
+
+    // repeating a previous state means player 1 wins in recursive combat
+    if !self.history.insert(self.decks.clone()) {
+      if self.rules == Rules::Normal {
+        let error_msg = format!("The game repeats itself after round {}", self.round);
+        return Err(AocError::NoSolution(error_msg));
+      }
+      self.winner = Some(Player::One);
+      return Ok(None);
+    }
+
+    // neither deck is empty, or there would be a winner already
+    let card1 = self.decks[0].pop_front().unwrap();
+    let card2 = self.decks[1].pop_front().unwrap();
+
+    let round_winner = if self.rules == Rules::Recursive
+      && self.decks[0].len() as u64 >= card1
+      && self.decks[1].len() as u64 >= card2
+    {
+      let sub_deck1: Deck = self.decks[0].iter().take(card1 as usize).copied().collect();
+      let sub_deck2: Deck = self.decks[1].iter().take(card2 as usize).copied().collect();
+      Game::new(&sub_deck1, &sub_deck2, Rules::Recursive).run()?
+    } else if card1 > card2 {
+      Player::One
+    } else {
+      Player::Two
+    };
+
+    let winning_cards = match round_winner {
+      Player::One => [card1, card2],
+      Player::Two => [card2, card1],
+    };
+    self.decks[round_winner.index()].extend(winning_cards.iter());
+    self.round += 1;
+    self.update_winner();
+
+    Ok(Some(round_winner))
+  }
+
+  fn update_winner(&mut self) {
+    if self.winner.is_some() {
+      return;
+    }
+
+    if self.decks[0].is_empty() {
+      self.winner = Some(Player::Two);
+    } else if self.decks[1].is_empty() {
+      self.winner = Some(Player::One);
+    }
+  }
+
+  pub fn run(&mut self) -> Result<Player, AocError> {
+    while self.winner.is_none() {
+      self.step()?;
+    }
+
+    Ok(self.winner.unwrap())
+  }
+}
+
+pub fn play(decks: &(Deck, Deck), rules: Rules) -> Result<(Player, u64), AocError> {
+  let mut game = Game::new(&decks.0, &decks.1, rules);
+  let winner = game.run()?;
+
+  Ok((winner, game.score(winner)))
+}
+
+fn parse_deck(first_line: usize, text: &str) -> Result<Deck, AocError> {
+  text
+    .lines()
+    .enumerate()
+    .skip(1) // NOTE: skip first line (it's a label)
+    .map(|(i, x)| {
+      x.parse::<u64>().map_err(|_| {
+        let error_msg = format!("Invalid card `{}`", x);
+        AocError::parse(first_line + i, 1, &error_msg)
+      })
+    })
+    .collect()
+}
+
+#[aoc_generator(day22)]
+pub fn parse_input(input: &str) -> Result<(Deck, Deck), AocError> {
+  let mut sections = blocks(input);
+  let mut next_deck = |player: usize| {
+    let (first_line, text) = sections
+      .next()
+      .ok_or_else(|| AocError::InvalidInput(format!("No deck for player {}", player)))?;
+    parse_deck(first_line, text)
+  };
+
+  let player1 = next_deck(1)?;
+  let player2 = next_deck(2)?;
+
+  Ok((player1, player2))
+}
+
+#[aoc(day22, part1)]
+pub fn solve_part1(decks: &(Deck, Deck)) -> Result<u64, AocError> {
+  play(decks, Rules::Normal).map(|x| x.1)
+}
+
+#[aoc(day22, part2)]
+pub fn solve_part2(decks: &(Deck, Deck)) -> Result<u64, AocError> {
+  play(decks, Rules::Recursive).map(|x| x.1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn build_deck(cards: &[u64]) -> Deck {
+    cards.iter().copied().collect()
+  }
+
+  fn example() -> (Deck, Deck) {
+    (build_deck(&[9, 2, 6, 3, 1]), build_deck(&[5, 8, 4, 7, 10]))
+  }
+
+  #[test]
+  fn test_parse_input() {
+    let input = "Player 1:
+9
+2
+6
+3
+1
+
+Player 2:
+5
+8
+4
+7
+10
+";
+    assert_eq!(parse_input(input), Ok(example()));
+    assert!(parse_input("Player 1:\n9\n2").is_err());
+    assert!(parse_input("Player 1:\na\n\nPlayer 2:\n1").is_err());
+    assert_eq!(
+      parse_input("Player 1:\n9\n\nPlayer 2:\n1\n-2"),
+      Err(AocError::parse(6, 1, "Invalid card `-2`"))
+    );
+  }
+
+  #[test]
+  fn test_step() {
+    let (deck1, deck2) = example();
+    let mut game = Game::new(&deck1, &deck2, Rules::Normal);
+
+    assert_eq!(game.step(), Ok(Some(Player::One)));
+    assert_eq!(game.round(), 1);
+    assert_eq!(game.deck(Player::One), &build_deck(&[2, 6, 3, 1, 9, 5]));
+    assert_eq!(game.deck(Player::Two), &build_deck(&[8, 4, 7, 10]));
+
+    assert_eq!(game.step(), Ok(Some(Player::Two)));
+    assert_eq!(game.deck(Player::One), &build_deck(&[6, 3, 1, 9, 5]));
+    assert_eq!(game.deck(Player::Two), &build_deck(&[4, 7, 10, 8, 2]));
+  }
+
+  #[test]
+  fn test_run() {
+    let (deck1, deck2) = example();
+    let mut game = Game::new(&deck1, &deck2, Rules::Normal);
+
+    assert_eq!(game.run(), Ok(Player::Two));
+    assert_eq!(game.round(), 29);
+    assert_eq!(game.winner(), Some(Player::Two));
+    assert_eq!(game.step(), Ok(None));
+    assert_eq!(
+      game.deck(Player::Two),
+      &build_deck(&[3, 2, 10, 6, 8, 5, 9, 4, 7, 1])
+    );
+  }
+
+  #[test]
+  fn test_recursive_loop_detection() {
+    let mut game = Game::new(
+      &build_deck(&[43, 19]),
+      &build_deck(&[2, 29, 14]),
+      Rules::Recursive,
+    );
+    assert_eq!(game.run(), Ok(Player::One));
+  }
+
+  #[test]
+  fn test_normal_loop_detection() {
+    let decks = (build_deck(&[43, 19]), build_deck(&[2, 29, 14]));
+    let mut game = Game::new(&decks.0, &decks.1, Rules::Normal);
+    let repeated = AocError::NoSolution("The game repeats itself after round 6".to_string());
+    assert_eq!(game.run(), Err(repeated.clone()));
+    assert_eq!(game.round(), 6);
+    assert_eq!(game.winner(), None);
+    assert_eq!(game.step(), Err(repeated));
+    assert!(play(&decks, Rules::Normal).is_err());
+    assert!(solve_part1(&decks).is_err());
+  }
+
+  #[test]
+  fn test_run_with_empty_deck() {
+    let mut game = Game::new(&build_deck(&[]), &build_deck(&[1]), Rules::Normal);
+    assert_eq!(game.run(), Ok(Player::Two));
+    assert_eq!(game.round(), 0);
+  }
+
+  #[test]
+  fn test_play() {
+    assert_eq!(play(&example(), Rules::Normal), Ok((Player::Two, 306)));
+    assert_eq!(play(&example(), Rules::Recursive), Ok((Player::Two, 291)));
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    assert_eq!(solve_part1(&example()), Ok(306));
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    assert_eq!(solve_part2(&example()), Ok(291));
+  }
+}
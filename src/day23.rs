@@ -0,0 +1,216 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::error::AocError;
+
+// a circular linked list stored as a flat array: `next[label]` is the label
+// of the cup that follows it clockwise (index 0 is unused)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cups {
+  next: Vec<u32>,
+  current: u32,
+}
+
+impl Cups {
+  // the labels given are placed first, then the rest up to `total` in order
+  pub fn new(labels: &[u32], total: usize) -> Result<Self, AocError> {
+    let mut seen = vec![false; labels.len() + 1];
+    for &label in labels {
+      let index = label as usize;
+      if index == 0 || index > labels.len() || seen[index] {
+        return Err(AocError::InvalidInput(format!(
+          "Invalid cup label {}",
+          label
+        )));
+      }
+      seen[index] = true;
+    }
+
+    // with fewer cups, every cup but the current one is picked up, and no
+    // destination can be found
+    let total = total.max(labels.len());
+    if total < 4 {
+      return Err(AocError::InvalidInput(
+        "At least 4 cups are needed to play".to_string(),
+      ));
+    }
+
+    let order = labels
+      .iter()
+      .copied()
+      .chain((labels.len() as u32 + 1)..=(total as u32));
+
+    let mut next = vec![0; total + 1];
+    let mut first = None;
+    let mut previous: Option<u32> = None;
+    for label in order {
+      match previous {
+        Some(previous) => next[previous as usize] = label,
+        None => first = Some(label),
+      }
+      previous = Some(label);
+    }
+
+    // close the circle
+    let first = first.unwrap();
+    next[previous.unwrap() as usize] = first;
+
+    Ok(Cups {
+      next,
+      current: first,
+    })
+  }
+
+  fn max_label(&self) -> u32 {
+    (self.next.len() - 1) as u32
+  }
+
+  pub fn step(&mut self) {
+    // pick up the three cups after the current one
+    let picked1 = self.next[self.current as usize];
+    let picked2 = self.next[picked1 as usize];
+    let picked3 = self.next[picked2 as usize];
+    self.next[self.current as usize] = self.next[picked3 as usize];
+
+    let mut destination = self.current;
+    loop {
+      destination = if destination > 1 {
+        destination - 1
+      } else {
+        self.max_label()
+      };
+      if destination != picked1 && destination != picked2 && destination != picked3 {
+        break;
+      }
+    }
+
+    // place the picked up cups right after the destination
+    self.next[picked3 as usize] = self.next[destination as usize];
+    self.next[destination as usize] = picked1;
+
+    self.current = self.next[self.current as usize];
+  }
+
+  pub fn labels_after(&self, label: u32) -> impl Iterator<Item = u32> + '_ {
+    let mut cursor = label;
+    std::iter::from_fn(move || {
+      cursor = self.next[cursor as usize];
+      Some(cursor)
+    })
+    .take_while(move |&x| x != label)
+  }
+}
+
+fn play(labels: &[u32], total: usize, moves: usize) -> Result<Cups, AocError> {
+  let mut cups = Cups::new(labels, total)?;
+  for _ in 0..moves {
+    cups.step();
+  }
+
+  Ok(cups)
+}
+
+#[aoc_generator(day23)]
+pub fn parse_input(input: &str) -> Result<Vec<u32>, AocError> {
+  input
+    .trim_end()
+    .chars()
+    .enumerate()
+    .map(|(i, c)| {
+      c.to_digit(10).ok_or_else(|| {
+        let error_msg = format!("Invalid cup label `{}`", c);
+        AocError::parse(1, i + 1, &error_msg)
+      })
+    })
+    .collect()
+}
+
+#[aoc(day23, part1)]
+pub fn solve_part1(labels: &[u32]) -> Result<String, AocError> {
+  let cups = play(labels, labels.len(), 100)?;
+  Ok(cups.labels_after(1).map(|x| x.to_string()).collect())
+}
+
+#[aoc(day23, part2)]
+pub fn solve_part2(labels: &[u32]) -> Result<u64, AocError> {
+  let cups = play(labels, 1_000_000, 10_000_000)?;
+  Ok(cups.labels_after(1).take(2).map(|x| x as u64).product())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_input() {
+    assert_eq!(
+      parse_input("389125467\n"),
+      Ok(vec![3, 8, 9, 1, 2, 5, 4, 6, 7])
+    );
+    assert_eq!(
+      parse_input("38a"),
+      Err(AocError::parse(1, 3, "Invalid cup label `a`"))
+    );
+  }
+
+  #[test]
+  fn test_cups_new() {
+    let cups = Cups::new(&[3, 1, 2], 5).unwrap();
+    assert_eq!(cups.current, 3);
+    assert_eq!(cups.labels_after(3).collect::<Vec<u32>>(), vec![1, 2, 4, 5]);
+
+    assert!(Cups::new(&[3, 1, 1], 3).is_err());
+    assert!(Cups::new(&[4, 1, 2], 4).is_err());
+    assert!(Cups::new(&[], 0).is_err());
+    assert_eq!(
+      Cups::new(&[1, 2], 2),
+      Err(AocError::InvalidInput(
+        "At least 4 cups are needed to play".to_string()
+      ))
+    );
+    assert!(Cups::new(&[1, 2], 4).is_ok());
+  }
+
+  #[test]
+  fn test_step() {
+    let mut cups = Cups::new(&[3, 8, 9, 1, 2, 5, 4, 6, 7], 9).unwrap();
+
+    cups.step();
+    assert_eq!(cups.current, 2);
+    assert_eq!(
+      cups.labels_after(3).collect::<Vec<u32>>(),
+      vec![2, 8, 9, 1, 5, 4, 6, 7]
+    );
+
+    cups.step();
+    assert_eq!(cups.current, 5);
+    assert_eq!(
+      cups.labels_after(3).collect::<Vec<u32>>(),
+      vec![2, 5, 4, 6, 7, 8, 9, 1]
+    );
+  }
+
+  #[test]
+  fn test_play() {
+    let cups = play(&[3, 8, 9, 1, 2, 5, 4, 6, 7], 9, 10).unwrap();
+    assert_eq!(
+      cups.labels_after(1).collect::<Vec<u32>>(),
+      vec![9, 2, 6, 5, 8, 3, 7, 4]
+    );
+
+    assert!(play(&[1, 2], 2, 10).is_err());
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    assert_eq!(
+      solve_part1(&[3, 8, 9, 1, 2, 5, 4, 6, 7]),
+      Ok("67384529".to_string())
+    );
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    assert_eq!(solve_part2(&[3, 8, 9, 1, 2, 5, 4, 6, 7]), Ok(149245887792));
+  }
+}
@@ -0,0 +1,208 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::error::AocError;
+use crate::hex::{Hex, HexDir};
+use std::collections::{HashMap, HashSet};
+
+pub type Path = Vec<HexDir>;
+
+// directions come without separators, so `n` and `s` always start a
+// two-letter token; errors point at the column in the line, but always at
+// the first line
+pub fn tokenize(text: &str) -> Result<Path, AocError> {
+  let mut path = vec![];
+  let indent = text.chars().take_while(|c| c.is_whitespace()).count();
+  let mut chars = text.trim().chars().enumerate();
+
+  while let Some((i, c)) = chars.next() {
+    let column = indent + i + 1;
+    let token = match c {
+      'n' | 's' => {
+        let (_, next) = chars.next().ok_or_else(|| {
+          let error_msg = format!("Incomplete direction `{}` in `{}`", c, text);
+          AocError::parse(1, column, &error_msg)
+        })?;
+        format!("{}{}", c, next)
+      }
+      _ => c.to_string(),
+    };
+    let dir = token
+      .parse::<HexDir>()
+      .map_err(|err| AocError::parse(1, column, &err))?;
+    path.push(dir);
+  }
+
+  Ok(path)
+}
+
+fn follow_path(path: &[HexDir]) -> Hex {
+  path
+    .iter()
+    .fold(Hex::default(), |hex, dir| hex.neighbor(*dir))
+}
+
+// returns the tiles that end up flipped to black
+pub fn flip_tiles(paths: &[Path]) -> HashSet<Hex> {
+  let mut black = HashSet::new();
+
+  for path in paths {
+    let tile = follow_path(path);
+    if !black.remove(&tile) {
+      black.insert(tile);
+    }
+  }
+
+  black
+}
+
+pub fn step(black: &HashSet<Hex>) -> HashSet<Hex> {
+  let mut black_neighbors: HashMap<Hex, usize> = HashMap::new();
+
+  for tile in black.iter() {
+    for neighbor in tile.neighbors() {
+      *black_neighbors.entry(neighbor).or_insert(0) += 1;
+    }
+  }
+
+  black_neighbors
+    .into_iter()
+    .filter(|(tile, count)| {
+      if black.contains(tile) {
+        *count == 1 || *count == 2
+      } else {
+        *count == 2
+      }
+    })
+    .map(|(tile, _)| tile)
+    .collect()
+}
+
+#[aoc_generator(day24)]
+pub fn parse_input(input: &str) -> Result<Vec<Path>, AocError> {
+  input
+    .lines()
+    .enumerate()
+    .map(|(i, line)| tokenize(line).map_err(|err| err.at_line(i + 1)))
+    .collect()
+}
+
+#[aoc(day24, part1)]
+pub fn solve_part1(paths: &[Path]) -> usize {
+  flip_tiles(paths).len()
+}
+
+#[aoc(day24, part2)]
+pub fn solve_part2(paths: &[Path]) -> usize {
+  let mut black = flip_tiles(paths);
+
+  for _ in 0..100 {
+    black = step(&black);
+  }
+
+  black.len()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EXAMPLE: &str = "sesenwnenenewseeswwswswwnenewsewsw
+neeenesenwnwwswnenewnwwsewnenwseswesw
+seswneswswsenwwnwse
+nwnwneseeswswnenewneswwnewseswneseene
+swweswneswnenwsewnwneneseenw
+eesenwseswswnenwswnwnwsewwnwsene
+sewnenenenesenwsewnenwwwse
+wenwwweseeeweswwwnwwe
+wsweesenenewnwwnwsenewsenwwsesesenwne
+neeswseenwwswnwswswnw
+nenwswwsewswnenenewsenwsenwnesesenew
+enewnwewneswsewnwswenweswnenwsenwsw
+sweneswneswneneenwnewenewwneswswnese
+swwesenesewenwneswnwwneseswwne
+enesenwswwswneneswsenwnewswseenwsese
+wnwnesenesenenwwnenwsewesewsesesew
+nenewswnwewswnenesenwnesewesw
+eneswnwswnwsenenwnwnwwseeswneewsenese
+neswnwewnwnwseenwseesewsenwsweewe
+wseweeenwnesenwwwswnew";
+
+  #[test]
+  fn test_tokenize() {
+    assert_eq!(
+      tokenize("esenee"),
+      Ok(vec![
+        HexDir::East,
+        HexDir::SouthEast,
+        HexDir::NorthEast,
+        HexDir::East
+      ])
+    );
+    assert_eq!(
+      tokenize("nwwswee"),
+      Ok(vec![
+        HexDir::NorthWest,
+        HexDir::West,
+        HexDir::SouthWest,
+        HexDir::East,
+        HexDir::East
+      ])
+    );
+    assert_eq!(
+      tokenize("esn"),
+      Err(AocError::parse(1, 2, "Unrecognized direction `sn`"))
+    );
+    assert_eq!(
+      tokenize("es"),
+      Err(AocError::parse(1, 2, "Incomplete direction `s` in `es`"))
+    );
+    assert_eq!(
+      tokenize(" ex"),
+      Err(AocError::parse(1, 3, "Unrecognized direction `x`"))
+    );
+  }
+
+  #[test]
+  fn test_parse_input() {
+    assert_eq!(
+      parse_input("e\nnw"),
+      Ok(vec![vec![HexDir::East], vec![HexDir::NorthWest]])
+    );
+    assert_eq!(
+      parse_input("e\nex"),
+      Err(AocError::parse(2, 2, "Unrecognized direction `x`"))
+    );
+  }
+
+  #[test]
+  fn test_follow_path() {
+    assert_eq!(follow_path(&tokenize("nwwswee").unwrap()), Hex::default());
+    assert_eq!(
+      follow_path(&tokenize("esew").unwrap()),
+      Hex::default().neighbor(HexDir::SouthEast)
+    );
+  }
+
+  #[test]
+  fn test_step() {
+    let mut black = flip_tiles(&parse_input(EXAMPLE).unwrap());
+
+    black = step(&black);
+    assert_eq!(black.len(), 15);
+    black = step(&black);
+    assert_eq!(black.len(), 12);
+    black = step(&black);
+    assert_eq!(black.len(), 25);
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    assert_eq!(solve_part1(&parse_input(EXAMPLE).unwrap()), 10);
+  }
+
+  #[test]
+  fn test_solve_part2() {
+    assert_eq!(solve_part2(&parse_input(EXAMPLE).unwrap()), 2208);
+  }
+}
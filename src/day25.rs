@@ -0,0 +1,75 @@
+use aoc_runner_derive::aoc;
+use aoc_runner_derive::aoc_generator;
+
+use crate::error::{parse_lines, AocError};
+use crate::modular::{discrete_log, mod_pow};
+
+const SUBJECT: u64 = 7;
+const MODULUS: u64 = 20201227;
+
+pub fn find_loop_size(public_key: u64) -> Option<u64> {
+  discrete_log(SUBJECT, public_key, MODULUS)
+}
+
+pub fn transform(subject: u64, loop_size: u64) -> u64 {
+  mod_pow(subject, loop_size, MODULUS)
+}
+
+#[aoc_generator(day25)]
+pub fn parse_input(input: &str) -> Result<(u64, u64), AocError> {
+  let keys = parse_lines(input, |x| {
+    x.trim()
+      .parse::<u64>()
+      .map_err(|_| format!("Invalid public key `{}`", x))
+  })?;
+
+  match keys.as_slice() {
+    [card, door] => Ok((*card, *door)),
+    _ => {
+      let error_msg = format!("Expected 2 public keys, found {}", keys.len());
+      Err(AocError::InvalidInput(error_msg))
+    }
+  }
+}
+
+#[aoc(day25, part1)]
+pub fn solve_part1(keys: &(u64, u64)) -> Result<u64, AocError> {
+  let (card_key, door_key) = *keys;
+  let card_loop_size = find_loop_size(card_key)
+    .ok_or_else(|| AocError::NoSolution(format!("Could not find loop size for {}", card_key)))?;
+
+  Ok(transform(door_key, card_loop_size))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_input() {
+    assert_eq!(parse_input("5764801\n17807724\n"), Ok((5764801, 17807724)));
+    assert!(parse_input("5764801").is_err());
+    assert_eq!(
+      parse_input("5764801\nabc"),
+      Err(AocError::parse(2, 1, "Invalid public key `abc`"))
+    );
+  }
+
+  #[test]
+  fn test_find_loop_size() {
+    assert_eq!(find_loop_size(5764801), Some(8));
+    assert_eq!(find_loop_size(17807724), Some(11));
+  }
+
+  #[test]
+  fn test_transform() {
+    assert_eq!(transform(SUBJECT, 8), 5764801);
+    assert_eq!(transform(17807724, 8), 14897079);
+    assert_eq!(transform(5764801, 11), 14897079);
+  }
+
+  #[test]
+  fn test_solve_part1() {
+    assert_eq!(solve_part1(&(5764801, 17807724)), Ok(14897079));
+  }
+}
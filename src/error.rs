@@ -0,0 +1,156 @@
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+// lines and columns are 1-based; errors that only know the line point at
+// its first column
+#[derive(Debug, Clone, PartialEq)]
+pub enum AocError {
+  Parse {
+    line: usize,
+    column: usize,
+    message: String,
+  },
+  InvalidInput(String),
+  NoSolution(String),
+  Overflow(String),
+  MissingInput {
+    day: u32,
+    path: PathBuf,
+  },
+}
+
+impl AocError {
+  pub fn parse(line: usize, column: usize, message: &str) -> Self {
+    AocError::Parse {
+      line,
+      column,
+      message: message.to_string(),
+    }
+  }
+
+  // parsers of a single line don't know where that line is in the input, so
+  // their errors are moved to the right line afterwards
+  pub fn at_line(self, line: usize) -> Self {
+    match self {
+      AocError::Parse {
+        column, message, ..
+      } => AocError::Parse {
+        line,
+        column,
+        message,
+      },
+      other => other,
+    }
+  }
+}
+
+impl fmt::Display for AocError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      AocError::Parse {
+        line,
+        column,
+        message,
+      } => write!(
+        f,
+        "Parse error at line {}, column {}: {}",
+        line, column, message
+      ),
+      AocError::InvalidInput(message) => write!(f, "Invalid input: {}", message),
+      AocError::NoSolution(message) => write!(f, "No solution: {}", message),
+      AocError::Overflow(message) => write!(f, "Overflow: {}", message),
+      AocError::MissingInput { day, path } => {
+        write!(
+          f,
+          "No input for day {}, expected at {}",
+          day,
+          path.display()
+        )
+      }
+    }
+  }
+}
+
+impl Error for AocError {}
+
+// parses every line of the input, reporting failures at the line they
+// happened
+pub fn parse_lines<T, E, F>(input: &str, parser: F) -> Result<Vec<T>, AocError>
+where
+  E: fmt::Display,
+  F: Fn(&str) -> Result<T, E>,
+{
+  input
+    .lines()
+    .enumerate()
+    .map(|(i, line)| parser(line).map_err(|err| AocError::parse(i + 1, 1, &err.to_string())))
+    .collect()
+}
+
+// splits the input in blank-line separated blocks, each paired with the line
+// it starts at
+pub fn blocks(input: &str) -> impl Iterator<Item = (usize, &str)> {
+  let mut line = 1;
+  input.split("\n\n").map(move |block| {
+    let start = line;
+    line += block.matches('\n').count() + 2;
+    (start, block)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_display() {
+    assert_eq!(
+      AocError::parse(3, 7, "Unexpected `x`").to_string(),
+      "Parse error at line 3, column 7: Unexpected `x`"
+    );
+    assert_eq!(
+      AocError::NoSolution("No pair adds up to 2020".to_string()).to_string(),
+      "No solution: No pair adds up to 2020"
+    );
+    assert_eq!(
+      AocError::MissingInput {
+        day: 8,
+        path: PathBuf::from("inputs/day8.txt")
+      }
+      .to_string(),
+      "No input for day 8, expected at inputs/day8.txt"
+    );
+  }
+
+  #[test]
+  fn test_at_line() {
+    assert_eq!(
+      AocError::parse(1, 4, "Oops").at_line(5),
+      AocError::parse(5, 4, "Oops")
+    );
+    assert_eq!(
+      AocError::Overflow("Too big".to_string()).at_line(5),
+      AocError::Overflow("Too big".to_string())
+    );
+  }
+
+  #[test]
+  fn test_parse_lines() {
+    assert_eq!(parse_lines("1\n2\n3", str::parse::<u32>), Ok(vec![1, 2, 3]));
+    assert_eq!(
+      parse_lines("1\nx\n3", |line| line
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid number `{}`", line))),
+      Err(AocError::parse(2, 1, "Invalid number `x`"))
+    );
+  }
+
+  #[test]
+  fn test_blocks() {
+    assert_eq!(
+      blocks("a\nb\n\nc\n\nd\ne\nf\n\ng").collect::<Vec<_>>(),
+      vec![(1, "a\nb"), (4, "c"), (6, "d\ne\nf"), (10, "g")]
+    );
+  }
+}
@@ -0,0 +1,154 @@
+use std::ops::Add;
+use std::str::FromStr;
+
+// directions in a hex grid with pointy-topped cells (east and west are
+// horizontal neighbors)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDir {
+  East,
+  SouthEast,
+  SouthWest,
+  West,
+  NorthWest,
+  NorthEast,
+}
+
+impl HexDir {
+  pub const ALL: [HexDir; 6] = [
+    HexDir::East,
+    HexDir::SouthEast,
+    HexDir::SouthWest,
+    HexDir::West,
+    HexDir::NorthWest,
+    HexDir::NorthEast,
+  ];
+
+  pub fn to_delta(self) -> Hex {
+    match self {
+      Self::East => Hex::new(1, 0),
+      Self::SouthEast => Hex::new(0, 1),
+      Self::SouthWest => Hex::new(-1, 1),
+      Self::West => Hex::new(-1, 0),
+      Self::NorthWest => Hex::new(0, -1),
+      Self::NorthEast => Hex::new(1, -1),
+    }
+  }
+}
+
+impl FromStr for HexDir {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    match text {
+      "e" => Ok(HexDir::East),
+      "se" => Ok(HexDir::SouthEast),
+      "sw" => Ok(HexDir::SouthWest),
+      "w" => Ok(HexDir::West),
+      "nw" => Ok(HexDir::NorthWest),
+      "ne" => Ok(HexDir::NorthEast),
+      _ => Err(format!("Unrecognized direction `{}`", text)),
+    }
+  }
+}
+
+// axial coordinates: `q` grows towards the east, `r` towards the south-east
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hex {
+  pub q: i64,
+  pub r: i64,
+}
+
+impl Hex {
+  pub fn new(q: i64, r: i64) -> Self {
+    Hex { q, r }
+  }
+
+  // cube coordinates (x, y, z), where x + y + z == 0
+  pub fn to_cube(self) -> (i64, i64, i64) {
+    (self.q, -self.q - self.r, self.r)
+  }
+
+  pub fn from_cube(cube: (i64, i64, i64)) -> Self {
+    Hex::new(cube.0, cube.2)
+  }
+
+  pub fn neighbor(self, dir: HexDir) -> Self {
+    self + dir.to_delta()
+  }
+
+  pub fn neighbors(self) -> impl Iterator<Item = Hex> {
+    HexDir::ALL.iter().map(move |&dir| self.neighbor(dir))
+  }
+
+  pub fn distance(self, other: Hex) -> i64 {
+    let (x1, y1, z1) = self.to_cube();
+    let (x2, y2, z2) = other.to_cube();
+    ((x1 - x2).abs() + (y1 - y2).abs() + (z1 - z2).abs()) / 2
+  }
+}
+
+impl Add for Hex {
+  type Output = Hex;
+
+  fn add(self, other: Hex) -> Hex {
+    Hex::new(self.q + other.q, self.r + other.r)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_hex_dir_from_str() {
+    assert_eq!("e".parse::<HexDir>(), Ok(HexDir::East));
+    assert_eq!("se".parse::<HexDir>(), Ok(HexDir::SouthEast));
+    assert_eq!("sw".parse::<HexDir>(), Ok(HexDir::SouthWest));
+    assert_eq!("w".parse::<HexDir>(), Ok(HexDir::West));
+    assert_eq!("nw".parse::<HexDir>(), Ok(HexDir::NorthWest));
+    assert_eq!("ne".parse::<HexDir>(), Ok(HexDir::NorthEast));
+    assert!("n".parse::<HexDir>().is_err());
+  }
+
+  #[test]
+  fn test_deltas_cancel_out() {
+    let total = HexDir::ALL
+      .iter()
+      .fold(Hex::default(), |hex, dir| hex.neighbor(*dir));
+    assert_eq!(total, Hex::default());
+
+    let origin = Hex::default();
+    assert_eq!(
+      origin
+        .neighbor(HexDir::NorthEast)
+        .neighbor(HexDir::SouthEast)
+        .neighbor(HexDir::West),
+      origin
+    );
+  }
+
+  #[test]
+  fn test_cube_coordinates() {
+    let hex = Hex::new(2, -3);
+    let (x, y, z) = hex.to_cube();
+    assert_eq!(x + y + z, 0);
+    assert_eq!(Hex::from_cube((x, y, z)), hex);
+  }
+
+  #[test]
+  fn test_neighbors() {
+    let hex = Hex::new(3, 4);
+    let neighbors: Vec<Hex> = hex.neighbors().collect();
+    assert_eq!(neighbors.len(), 6);
+    assert!(neighbors.iter().all(|&other| hex.distance(other) == 1));
+  }
+
+  #[test]
+  fn test_distance() {
+    let origin = Hex::default();
+    assert_eq!(origin.distance(origin), 0);
+    assert_eq!(origin.distance(Hex::new(2, 0)), 2);
+    assert_eq!(origin.distance(Hex::new(2, -2)), 2);
+    assert_eq!(origin.distance(Hex::new(2, 1)), 3);
+  }
+}
@@ -0,0 +1,212 @@
+use crate::error::AocError;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_DIR: &str = "inputs";
+pub const CONFIG_FILE: &str = "aoc.conf";
+pub const DIR_VAR: &str = "AOC_INPUT_DIR";
+pub const USER_VAR: &str = "AOC_USER";
+
+// a local store of puzzle inputs, with the input for day N at `dir/dayN.txt`;
+// when a user is set, inputs are at `dir/user/dayN.txt` instead, so the
+// inputs of several accounts can be kept side by side
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputStore {
+  dir: PathBuf,
+  user: Option<String>,
+}
+
+impl InputStore {
+  pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+    InputStore {
+      dir: dir.into(),
+      user: None,
+    }
+  }
+
+  pub fn with_user(self, user: &str) -> Self {
+    InputStore {
+      user: Some(user.to_string()),
+      ..self
+    }
+  }
+
+  // settings come from `aoc.conf` in the current directory, if present, and
+  // are overridden by the `AOC_INPUT_DIR` and `AOC_USER` env vars
+  pub fn from_env() -> Result<Self, AocError> {
+    let config = match fs::read_to_string(CONFIG_FILE) {
+      Ok(config) => config,
+      Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+      Err(err) => {
+        let error_msg = format!("Could not read {}: {}", CONFIG_FILE, err);
+        return Err(AocError::InvalidInput(error_msg));
+      }
+    };
+
+    let vars = (env::var(DIR_VAR).ok(), env::var(USER_VAR).ok());
+    from_settings(&config, vars)
+  }
+
+  // the directory with the inputs of the current user
+  pub fn dir(&self) -> PathBuf {
+    match &self.user {
+      Some(user) => self.dir.join(user),
+      None => self.dir.clone(),
+    }
+  }
+
+  pub fn path(&self, day: u32) -> PathBuf {
+    self.dir().join(format!("day{}.txt", day))
+  }
+
+  pub fn has_input(&self, day: u32) -> bool {
+    self.path(day).is_file()
+  }
+
+  pub fn load(&self, day: u32) -> Result<String, AocError> {
+    let path = self.path(day);
+    read_input(&path).map_err(|err| match err {
+      AocError::InvalidInput(_) if !path.exists() => AocError::MissingInput { day, path },
+      other => other,
+    })
+  }
+}
+
+impl Default for InputStore {
+  fn default() -> Self {
+    InputStore::new(DEFAULT_DIR)
+  }
+}
+
+// the config has one `key = value` setting per line, with `input_dir` and
+// `user` as the only keys; blank lines and lines starting with `#` are ignored
+fn from_settings(
+  config: &str,
+  (dir_var, user_var): (Option<String>, Option<String>),
+) -> Result<InputStore, AocError> {
+  let mut dir: Option<String> = None;
+  let mut user: Option<String> = None;
+
+  for (i, line) in config.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+
+    let (key, value) = match line.find('=') {
+      Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+      None => {
+        let error_msg = format!("Expected `key = value`, found `{}`", line);
+        return Err(AocError::parse(i + 1, 1, &error_msg));
+      }
+    };
+    match key {
+      "input_dir" => dir = Some(value.to_string()),
+      "user" => user = Some(value.to_string()),
+      _ => {
+        let error_msg = format!("Unknown setting `{}`", key);
+        return Err(AocError::parse(i + 1, 1, &error_msg));
+      }
+    }
+  }
+
+  let dir = dir_var.or(dir).unwrap_or_else(|| DEFAULT_DIR.to_string());
+  let store = InputStore::new(dir);
+  Ok(match user_var.or(user).filter(|x| !x.is_empty()) {
+    Some(user) => store.with_user(&user),
+    None => store,
+  })
+}
+
+// inputs saved on Windows or by hand may have CRLF line endings or trailing
+// blank lines, which none of the generators expect
+pub fn normalize(input: &str) -> String {
+  input
+    .replace("\r\n", "\n")
+    .trim_end_matches('\n')
+    .to_string()
+}
+
+// reads and normalizes an input file outside of the store
+pub fn read_input(path: &Path) -> Result<String, AocError> {
+  fs::read_to_string(path)
+    .map(|input| normalize(&input))
+    .map_err(|err| AocError::InvalidInput(format!("Could not read {}: {}", path.display(), err)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_normalize() {
+    assert_eq!(normalize("1\r\n2\r\n\r\n3\r\n\r\n"), "1\n2\n\n3");
+    assert_eq!(normalize("1\n2\n"), "1\n2");
+    assert_eq!(normalize(""), "");
+  }
+
+  #[test]
+  fn test_path() {
+    let store = InputStore::new("inputs");
+    assert_eq!(store.path(8), Path::new("inputs/day8.txt"));
+    let store = store.with_user("alice");
+    assert_eq!(store.dir(), Path::new("inputs/alice"));
+    assert_eq!(store.path(25), Path::new("inputs/alice/day25.txt"));
+  }
+
+  #[test]
+  fn test_from_settings() {
+    let config = "# shared inputs\ninput_dir = /tmp/aoc\n\nuser = alice\n";
+    assert_eq!(
+      from_settings(config, (None, None)),
+      Ok(InputStore::new("/tmp/aoc").with_user("alice"))
+    );
+    assert_eq!(
+      from_settings(config, (Some("other".to_string()), Some("bob".to_string()))),
+      Ok(InputStore::new("other").with_user("bob"))
+    );
+    assert_eq!(
+      from_settings("", (None, Some("".to_string()))),
+      Ok(InputStore::default())
+    );
+  }
+
+  #[test]
+  fn test_from_settings_errors() {
+    assert_eq!(
+      from_settings("user = alice\ninput_dir", (None, None)),
+      Err(AocError::parse(
+        2,
+        1,
+        "Expected `key = value`, found `input_dir`"
+      ))
+    );
+    assert_eq!(
+      from_settings("year = 2020", (None, None)),
+      Err(AocError::parse(1, 1, "Unknown setting `year`"))
+    );
+  }
+
+  #[test]
+  fn test_load() {
+    let dir = env::temp_dir().join(format!("aoc-2020-inputs-{}", std::process::id()));
+    let store = InputStore::new(&dir).with_user("alice");
+    fs::create_dir_all(store.dir()).unwrap();
+    fs::write(store.path(1), "1721\r\n979\r\n").unwrap();
+
+    assert!(store.has_input(1));
+    assert_eq!(store.load(1), Ok("1721\n979".to_string()));
+    assert!(!store.has_input(2));
+    assert_eq!(
+      store.load(2),
+      Err(AocError::MissingInput {
+        day: 2,
+        path: dir.join("alice/day2.txt")
+      })
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -1,5 +1,16 @@
+// the tests compare booleans with `assert_eq!` and build fixtures with `vec!`
+#![cfg_attr(test, allow(clippy::bool_assert_comparison, clippy::useless_vec))]
+
 use aoc_runner_derive::aoc_lib;
 
+pub mod answers;
+pub mod assignment;
+pub mod error;
+pub mod hex;
+pub mod inputs;
+pub mod modular;
+pub mod registry;
+
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -16,5 +27,14 @@ pub mod day13;
 pub mod day14;
 pub mod day15;
 pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
 
 aoc_lib! { year = 2020 }
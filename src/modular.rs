@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+// products are computed in u128 so they never overflow
+pub fn mod_mul(a: u64, b: u64, modulus: u64) -> u64 {
+  ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+// exponentiation by squaring
+pub fn mod_pow(base: u64, exp: u64, modulus: u64) -> u64 {
+  if modulus == 1 {
+    return 0;
+  }
+
+  let mut result = 1;
+  let mut base = base % modulus;
+  let mut exp = exp;
+
+  while exp > 0 {
+    if exp & 1 == 1 {
+      result = mod_mul(result, base, modulus);
+    }
+    base = mod_mul(base, base, modulus);
+    exp >>= 1;
+  }
+
+  result
+}
+
+// smallest `x` such that `base^x == target (mod modulus)`, using the
+// baby-step giant-step algorithm; `base` and `modulus` must be coprime
+pub fn discrete_log(base: u64, target: u64, modulus: u64) -> Option<u64> {
+  if modulus == 0 {
+    return None;
+  }
+
+  let target = target % modulus;
+  if target == 1 % modulus {
+    return Some(0);
+  }
+
+  let m = (modulus as f64).sqrt().ceil() as u64;
+
+  // baby steps: target * base^j -> j (the largest j wins, so x is minimal)
+  let mut baby_steps: HashMap<u64, u64> = HashMap::with_capacity(m as usize);
+  let mut value = target;
+  for j in 0..m {
+    baby_steps.insert(value, j);
+    value = mod_mul(value, base, modulus);
+  }
+
+  // giant steps: base^(i * m) == target * base^j  =>  x = i * m - j
+  let giant = mod_pow(base, m, modulus);
+  let mut value = 1;
+  for i in 1..=m {
+    value = mod_mul(value, giant, modulus);
+    if let Some(j) = baby_steps.get(&value) {
+      return Some(i * m - j);
+    }
+  }
+
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_mod_mul() {
+    assert_eq!(mod_mul(7, 8, 10), 6);
+    assert_eq!(mod_mul(u64::MAX, u64::MAX, 1_000_000_007), 114_944_269);
+  }
+
+  #[test]
+  fn test_mod_pow() {
+    assert_eq!(mod_pow(7, 0, 20201227), 1);
+    assert_eq!(mod_pow(7, 8, 20201227), 5764801);
+    assert_eq!(mod_pow(2, 10, 1000), 24);
+    assert_eq!(mod_pow(5, 3, 1), 0);
+  }
+
+  #[test]
+  fn test_discrete_log() {
+    assert_eq!(discrete_log(7, 1, 20201227), Some(0));
+    assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+    assert_eq!(discrete_log(7, 17807724, 20201227), Some(11));
+    assert_eq!(discrete_log(3, 13, 17), Some(4));
+
+    // 2 only generates {1, 2, 4} modulo 7
+    assert_eq!(discrete_log(2, 3, 7), None);
+  }
+}
@@ -0,0 +1,160 @@
+use crate::error::AocError;
+use crate::*;
+
+// a part of a day, running both its generator and its solver
+#[derive(Debug, Clone, Copy)]
+pub struct Solution {
+  pub day: u32,
+  pub part: u32,
+  pub name: &'static str,
+  pub run: fn(&str) -> Result<String, AocError>,
+}
+
+// converts whatever a solver returns into a printable answer
+trait Answer {
+  fn into_answer(self) -> Result<String, AocError>;
+}
+
+macro_rules! impl_answer {
+  ($($t:ty),*) => {
+    $(
+      impl Answer for $t {
+        fn into_answer(self) -> Result<String, AocError> {
+          Ok(self.to_string())
+        }
+      }
+    )*
+  };
+}
+
+impl_answer!(u32, u64, usize, i64, String);
+
+impl<T: Answer> Answer for Result<T, AocError> {
+  fn into_answer(self) -> Result<String, AocError> {
+    self?.into_answer()
+  }
+}
+
+// for the days whose solvers take the raw input
+fn raw_input(input: &str) -> Result<&str, AocError> {
+  Ok(input)
+}
+
+macro_rules! registry {
+  ($(($day:expr, $part:expr, $name:expr, $generator:path, $solver:path)),* $(,)?) => {
+    const SOLUTIONS: &[Solution] = &[
+      $(
+        Solution {
+          day: $day,
+          part: $part,
+          name: $name,
+          run: |input| $solver(&$generator(input)?).into_answer(),
+        }
+      ),*
+    ];
+  };
+}
+
+registry! {
+  (1, 1, "Report Repair", day01::parse_day1, day01::solve_part1),
+  (1, 2, "Report Repair", day01::parse_day1, day01::solve_part2),
+  (2, 1, "Password Philosophy", day02::parse_input, day02::solve_part1),
+  (2, 2, "Password Philosophy", day02::parse_input, day02::solve_part2),
+  (3, 1, "Toboggan Trajectory", day03::parse_input, day03::solve_part1),
+  (3, 2, "Toboggan Trajectory", day03::parse_input, day03::solve_part2),
+  (4, 1, "Passport Processing", day04::parse_input, day04::solve_part1),
+  (4, 2, "Passport Processing", day04::parse_input, day04::solve_part2),
+  (5, 1, "Binary Boarding", day05::parse_input, day05::solve_part1),
+  (5, 2, "Binary Boarding", day05::parse_input, day05::solve_part2),
+  (6, 1, "Custom Customs", day06::parse_input_part1, day06::solve_part1),
+  (6, 2, "Custom Customs", day06::parse_input_part2, day06::solve_part2),
+  (7, 1, "Handy Haversacks", day07::parse_input, day07::solve_part1),
+  (7, 2, "Handy Haversacks", day07::parse_input, day07::solve_part2),
+  (8, 1, "Handheld Halting", day08::parse_input, day08::solve_part1),
+  (8, 2, "Handheld Halting", day08::parse_input, day08::solve_part2),
+  (9, 1, "Encoding Error", day09::parse_input, day09::solve_part1),
+  (9, 2, "Encoding Error", day09::parse_input, day09::solve_part2),
+  (10, 1, "Adapter Array", day10::parse_input, day10::solve_part1),
+  (10, 2, "Adapter Array", day10::parse_input, day10::solve_part2),
+  (11, 1, "Seating System", raw_input, day11::solve_part1),
+  (11, 2, "Seating System", raw_input, day11::solve_part2),
+  (12, 1, "Rain Risk", day12::parse_input, day12::solve_part1),
+  (12, 2, "Rain Risk", day12::parse_input, day12::solve_part2),
+  (13, 1, "Shuttle Search", day13::parse_input, day13::solve_part1),
+  (13, 2, "Shuttle Search", day13::parse_input, day13::solve_part2),
+  (14, 1, "Docking Data", day14::parse_input, day14::solve_part1),
+  (14, 2, "Docking Data", day14::parse_input, day14::solve_part2),
+  (15, 1, "Rambunctious Recitation", day15::parse_input, day15::solve_part1),
+  (15, 2, "Rambunctious Recitation", day15::parse_input, day15::solve_part2),
+  (16, 1, "Ticket Translation", day16::parse_input, day16::solve_part1),
+  (16, 2, "Ticket Translation", day16::parse_input, day16::solve_part2),
+  (17, 1, "Conway Cubes", day17::parse_input, day17::solve_part1),
+  (17, 2, "Conway Cubes", day17::parse_input, day17::solve_part2),
+  (18, 1, "Operation Order", day18::parse_input, day18::solve_part1),
+  (18, 2, "Operation Order", day18::parse_input, day18::solve_part2),
+  (19, 1, "Monster Messages", day19::parse_input, day19::solve_part1),
+  (19, 2, "Monster Messages", day19::parse_input, day19::solve_part2),
+  (20, 1, "Jurassic Jigsaw", day20::parse_input, day20::solve_part1),
+  (20, 2, "Jurassic Jigsaw", day20::parse_input, day20::solve_part2),
+  (21, 1, "Allergen Assessment", day21::parse_input, day21::solve_part1),
+  (21, 2, "Allergen Assessment", day21::parse_input, day21::solve_part2),
+  (22, 1, "Crab Combat", day22::parse_input, day22::solve_part1),
+  (22, 2, "Crab Combat", day22::parse_input, day22::solve_part2),
+  (23, 1, "Crab Cups", day23::parse_input, day23::solve_part1),
+  (23, 2, "Crab Cups", day23::parse_input, day23::solve_part2),
+  (24, 1, "Lobby Layout", day24::parse_input, day24::solve_part1),
+  (24, 2, "Lobby Layout", day24::parse_input, day24::solve_part2),
+  (25, 1, "Combo Breaker", day25::parse_input, day25::solve_part1),
+}
+
+// sorted by day, then by part
+pub fn solutions() -> impl Iterator<Item = Solution> {
+  SOLUTIONS.iter().copied()
+}
+
+// every part of the day when no part is given
+pub fn find_solutions(day: u32, part: Option<u32>) -> impl Iterator<Item = Solution> {
+  solutions()
+    .filter(move |solution| solution.day == day && (part.is_none() || part == Some(solution.part)))
+}
+
+pub fn find_solution(day: u32, part: u32) -> Option<Solution> {
+  find_solutions(day, Some(part)).next()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_solutions_are_sorted_and_unique() {
+    let keys: Vec<(u32, u32)> = solutions().map(|x| (x.day, x.part)).collect();
+    assert_eq!(keys.len(), 49);
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+  }
+
+  #[test]
+  fn test_find_solutions() {
+    assert_eq!(find_solutions(1, None).count(), 2);
+    assert_eq!(find_solutions(1, Some(2)).count(), 1);
+    assert_eq!(find_solutions(26, None).count(), 0);
+    assert!(find_solution(25, 2).is_none());
+    assert_eq!(find_solution(8, 1).unwrap().name, "Handheld Halting");
+  }
+
+  #[test]
+  fn test_run() {
+    let solution = find_solution(1, 1).unwrap();
+    assert_eq!(
+      (solution.run)("1721\n979\n366\n299\n675\n1456"),
+      Ok("514579".to_string())
+    );
+    assert_eq!(
+      (solution.run)("1721\nabc"),
+      Err(AocError::parse(2, 1, "Invalid expense `abc`"))
+    );
+
+    let solution = find_solution(11, 1).unwrap();
+    assert_eq!((solution.run)("L.L\n.L."), Ok("3".to_string()));
+  }
+}
@@ -0,0 +1,59 @@
+use aoc_2020::answers::{parse_answers, ExpectedAnswer};
+use aoc_2020::inputs::{self, InputStore};
+use aoc_2020::registry;
+
+// puzzle inputs are personal, so they are not part of the repository; only
+// the days with a local input file are checked, and the expected answers are
+// read from `answers.txt` next to them
+#[test]
+fn test_answers() {
+  let store = InputStore::from_env().unwrap_or_else(|err| panic!("{}", err));
+  let manifest = inputs::read_input(&store.dir().join("answers.txt")).unwrap_or_default();
+  let answers = parse_answers(&manifest).unwrap_or_else(|err| panic!("answers.txt: {}", err));
+  let mut failures: Vec<String> = vec![];
+
+  // answers for parts that don't exist are most likely typos
+  for expected in &answers {
+    if registry::find_solution(expected.day, expected.part).is_none() {
+      failures.push(format!(
+        "Day {} - Part {}: not implemented",
+        expected.day, expected.part
+      ));
+    }
+  }
+
+  for solution in registry::solutions() {
+    let label = format!("Day {} - Part {}", solution.day, solution.part);
+    if !store.has_input(solution.day) {
+      let path = store.path(solution.day);
+      eprintln!("{}: skipped, {} not found", label, path.display());
+      continue;
+    }
+    let input = match store.load(solution.day) {
+      Ok(input) => input,
+      Err(err) => {
+        failures.push(format!("{}: {}", label, err));
+        continue;
+      }
+    };
+    let expected = answers
+      .iter()
+      .find(|x| x.day == solution.day && x.part == solution.part)
+      .map(|ExpectedAnswer { answer, .. }| answer);
+    let expected = match expected {
+      Some(expected) => expected,
+      None => {
+        eprintln!("{}: skipped, no expected answer in answers.txt", label);
+        continue;
+      }
+    };
+
+    match (solution.run)(&input) {
+      Ok(answer) if &answer == expected => {}
+      Ok(answer) => failures.push(format!("{}: expected {}, got {}", label, expected, answer)),
+      Err(err) => failures.push(format!("{}: {}", label, err)),
+    }
+  }
+
+  assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}